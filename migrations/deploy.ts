@@ -8,5 +8,7 @@ module.exports = async function (provider) {
   // Configure client to use the provider.
   anchor.setProvider(provider);
 
+  // Account layouts of both programs changed without a migration path, so deployments made
+  // before reserves, provider payouts and job lifecycle tracking need to be redeployed fresh.
   // Add your deploy script here.
 };
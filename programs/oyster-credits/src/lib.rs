@@ -3,6 +3,11 @@ use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer, Burn, MintTo}
 
 declare_id!("5ngFsa5kHNM18VBaFo5qSus9ajSGsX92JpX9pfbBjCDJ");
 
+// Number of provider redemption tiers
+const MAX_TIERS: usize = 8;
+// Redemption rates are expressed in basis points of face value
+const BPS_DENOMINATOR: u64 = 10_000;
+
 #[program]
 pub mod oyster_credits {
     use super::*;
@@ -10,21 +15,270 @@ pub mod oyster_credits {
     pub fn initialize(
         ctx: Context<Initialize>,
         admin: Pubkey,
-        oyster_market: Pubkey,
-        usdc_mint: Pubkey
+        oyster_market: Pubkey
     ) -> Result<()> {
         let state = &mut ctx.accounts.state;
         require!(!state.initialized, ErrorCodes::AlreadyInitialized);
 
         state.admin = admin;
         state.oyster_market = oyster_market;
-        state.usdc_mint = usdc_mint;
+        state.tier_redemption_bps = [BPS_DENOMINATOR as u16; MAX_TIERS];
         state.initialized = true;
+
+        emit!(CreditsInitialized {
+            seq: state.next_event_seq(),
+            admin,
+            oyster_market,
+        });
+        Ok(())
+    }
+
+    // Add a stablecoin reserve that backs credit redemptions in its mint
+    pub fn add_reserve(ctx: Context<AddReserve>) -> Result<()> {
+        require!(ctx.accounts.state.admin == *ctx.accounts.authority.key, ErrorCodes::OnlyAdmin);
+        // Credits redeem one to one, so the reserve mint must use the credit decimals
+        require!(
+            ctx.accounts.usdc_mint.decimals == ctx.accounts.credit_mint.decimals,
+            ErrorCodes::ReserveDecimalsMismatch
+        );
+
+        let reserve = &mut ctx.accounts.reserve;
+        reserve.mint = ctx.accounts.usdc_mint.key();
+        reserve.vault = ctx.accounts.program_usdc_token_account.key();
+
+        emit!(ReserveAdded {
+            seq: ctx.accounts.state.next_event_seq(),
+            mint: reserve.mint,
+            vault: reserve.vault,
+        });
+        Ok(())
+    }
+
+    // Fund a reserve vault
+    pub fn deposit_reserve(ctx: Context<DepositReserve>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCodes::InvalidAmount);
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.depositor_token_account.to_account_info(),
+            to: ctx.accounts.program_usdc_token_account.to_account_info(),
+            authority: ctx.accounts.depositor.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts
+        );
+        token::transfer(cpi_ctx, amount)?;
+
+        let reserve = &mut ctx.accounts.reserve;
+        reserve.total_deposited += amount;
+
+        emit!(ReserveDeposited {
+            seq: ctx.accounts.state.next_event_seq(),
+            mint: reserve.mint,
+            from: ctx.accounts.depositor.key(),
+            amount,
+        });
+        Ok(())
+    }
+
+    // Set the redemption rate of a provider tier
+    pub fn set_tier_redemption_rate(
+        ctx: Context<AdminAction>,
+        tier: u8,
+        redemption_bps: u16
+    ) -> Result<()> {
+        require!(ctx.accounts.state.admin == *ctx.accounts.authority.key, ErrorCodes::OnlyAdmin);
+        require!((tier as usize) < MAX_TIERS, ErrorCodes::InvalidTier);
+        require!(redemption_bps as u64 <= BPS_DENOMINATOR, ErrorCodes::InvalidRedemptionRate);
+
+        let state = &mut ctx.accounts.state;
+        state.tier_redemption_bps[tier as usize] = redemption_bps;
+
+        emit!(TierRedemptionRateUpdated {
+            seq: state.next_event_seq(),
+            tier,
+            redemption_bps,
+        });
+        Ok(())
+    }
+
+    // Assign a provider to a redemption tier
+    pub fn set_provider_tier(
+        ctx: Context<SetProviderTier>,
+        provider: Pubkey,
+        tier: u8
+    ) -> Result<()> {
+        require!(ctx.accounts.state.admin == *ctx.accounts.authority.key, ErrorCodes::OnlyAdmin);
+        require!((tier as usize) < MAX_TIERS, ErrorCodes::InvalidTier);
+
+        let provider_tier = &mut ctx.accounts.provider_tier;
+        provider_tier.provider = provider;
+        provider_tier.tier = tier;
+
+        emit!(ProviderTierUpdated {
+            seq: ctx.accounts.state.next_event_seq(),
+            provider,
+            tier,
+        });
+        Ok(())
+    }
+
+    // Choose whether redemption haircuts accrue to the subsidy pool or stay in reserve
+    pub fn set_haircut_to_subsidy(ctx: Context<AdminAction>, haircut_to_subsidy: bool) -> Result<()> {
+        require!(ctx.accounts.state.admin == *ctx.accounts.authority.key, ErrorCodes::OnlyAdmin);
+
+        let state = &mut ctx.accounts.state;
+        state.haircut_to_subsidy = haircut_to_subsidy;
+
+        emit!(HaircutDestinationUpdated {
+            seq: state.next_event_seq(),
+            haircut_to_subsidy,
+        });
+        Ok(())
+    }
+
+    // Pay out USDC accrued in the subsidy pool
+    pub fn withdraw_subsidy(ctx: Context<WithdrawSubsidy>, amount: u64) -> Result<()> {
+        require!(ctx.accounts.state.admin == *ctx.accounts.authority.key, ErrorCodes::OnlyAdmin);
+        require!(ctx.accounts.reserve.subsidy_pool >= amount, ErrorCodes::NotEnoughSubsidy);
+
+        ctx.accounts.reserve.subsidy_pool -= amount;
+
+        let usdc_mint: Pubkey = ctx.accounts.usdc_mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"program_usdc", usdc_mint.as_ref(), &[ctx.bumps.program_usdc_token_account]
+        ]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.program_usdc_token_account.to_account_info(),
+            to: ctx.accounts.receiver.to_account_info(),
+            authority: ctx.accounts.program_usdc_token_account.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts
+        ).with_signer(signer_seeds);
+        token::transfer(cpi_ctx, amount)?;
+
+        emit!(SubsidyWithdrawn {
+            seq: ctx.accounts.state.next_event_seq(),
+            mint: usdc_mint,
+            to: ctx.accounts.receiver.key(),
+            amount,
+        });
+        Ok(())
+    }
+
+    // Withdraw USDC from a reserve vault, leaving the subsidy pool untouched
+    pub fn withdraw_reserve(ctx: Context<WithdrawReserve>, amount: u64) -> Result<()> {
+        require!(ctx.accounts.state.admin == *ctx.accounts.authority.key, ErrorCodes::OnlyAdmin);
+        require!(amount > 0, ErrorCodes::InvalidAmount);
+
+        let available = ctx.accounts.program_usdc_token_account.amount
+            .saturating_sub(ctx.accounts.reserve.subsidy_pool);
+        require!(available >= amount, ErrorCodes::NotEnoughUSDC);
+
+        ctx.accounts.reserve.total_withdrawn += amount;
+
+        let usdc_mint: Pubkey = ctx.accounts.usdc_mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"program_usdc", usdc_mint.as_ref(), &[ctx.bumps.program_usdc_token_account]
+        ]];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.program_usdc_token_account.to_account_info(),
+            to: ctx.accounts.receiver.to_account_info(),
+            authority: ctx.accounts.program_usdc_token_account.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts
+        ).with_signer(signer_seeds);
+        token::transfer(cpi_ctx, amount)?;
+
+        emit!(ReserveWithdrawn {
+            seq: ctx.accounts.state.next_event_seq(),
+            mint: usdc_mint,
+            to: ctx.accounts.receiver.key(),
+            amount,
+        });
+        Ok(())
+    }
+
+    // Grant a minter role with a per-window allowance and a lifetime cap. Granting an existing or
+    // revoked role again resets it with the new limits.
+    pub fn add_minter(
+        ctx: Context<AddMinter>,
+        minter: Pubkey,
+        window_allowance: u64,
+        window_duration: u64,
+        lifetime_cap: u64
+    ) -> Result<()> {
+        require!(ctx.accounts.state.admin == *ctx.accounts.authority.key, ErrorCodes::OnlyAdmin);
+        require!(window_duration > 0, ErrorCodes::InvalidMintWindow);
+
+        let role = &mut ctx.accounts.minter_role;
+        role.minter = minter;
+        role.window_allowance = window_allowance;
+        role.window_duration = window_duration;
+        role.window_start = Clock::get()?.unix_timestamp as u64;
+        role.window_minted = 0;
+        role.lifetime_cap = lifetime_cap;
+        role.lifetime_minted = 0;
+        role.revoked = false;
+
+        emit!(MinterAdded {
+            seq: ctx.accounts.state.next_event_seq(),
+            minter,
+            role: role.key(),
+            window_allowance,
+            window_duration,
+            lifetime_cap,
+        });
         Ok(())
     }
 
+    // Revoke a minter role, disabling it until it is granted again
+    pub fn revoke_minter(ctx: Context<RevokeMinter>) -> Result<()> {
+        require!(ctx.accounts.state.admin == *ctx.accounts.authority.key, ErrorCodes::OnlyAdmin);
+
+        let role = &mut ctx.accounts.minter_role;
+        require!(!role.revoked, ErrorCodes::MinterRevoked);
+        role.revoked = true;
+
+        emit!(MinterRevoked {
+            seq: ctx.accounts.state.next_event_seq(),
+            minter: role.minter,
+            role: role.key(),
+        });
+        Ok(())
+    }
+
+    // Mint credits as a minter within its limits. The admin mints through a role of its own.
     pub fn mint(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
-        require!(ctx.accounts.state.admin == *ctx.accounts.signer.key, ErrorCodes::OnlyAdmin);
+        let role = &mut ctx.accounts.minter_role;
+        require!(!role.revoked, ErrorCodes::MinterRevoked);
+
+        // Start a new window once the current one has elapsed
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        if current_time >= role.window_start.saturating_add(role.window_duration) {
+            role.window_start = current_time;
+            role.window_minted = 0;
+        }
+
+        let window_minted = role.window_minted.checked_add(amount)
+            .filter(|minted| *minted <= role.window_allowance)
+            .ok_or(ErrorCodes::MintAllowanceExceeded)?;
+        let lifetime_minted = role.lifetime_minted.checked_add(amount)
+            .filter(|minted| *minted <= role.lifetime_cap)
+            .ok_or(ErrorCodes::MintCapExceeded)?;
+
+        role.window_minted = window_minted;
+        role.lifetime_minted = lifetime_minted;
+        let role_key = role.key();
 
         let signer_seeds: &[&[&[u8]]] = &[&[b"credit_mint", &[ctx.bumps.credit_mint]]];
 
@@ -40,6 +294,13 @@ pub mod oyster_credits {
         ).with_signer(signer_seeds);
         token::mint_to(cpi_ctx, amount)?;
 
+        emit!(CreditsMinted {
+            seq: ctx.accounts.state.next_event_seq(),
+            minter: ctx.accounts.signer.key(),
+            role: role_key,
+            to: ctx.accounts.token_account.key(),
+            amount,
+        });
         Ok(())
     }
 
@@ -60,14 +321,71 @@ pub mod oyster_credits {
         ).with_signer(signer_seeds);
         token::burn(cpi_ctx, amount)?;
 
+        emit!(CreditsBurned {
+            seq: ctx.accounts.state.next_event_seq(),
+            from: ctx.accounts.token_account.key(),
+            job: Pubkey::default(),
+            amount,
+        });
         Ok(())
     }
 
-    pub fn redeem_and_burn(ctx: Context<RedeemAndBurn>, amount: u64) -> Result<()> {
+    // Burns `amount` unused credits of market `job` held by the market, signed by the market's
+    // credit token account
+    pub fn burn_market_credits(ctx: Context<BurnMarketCredits>, amount: u64, job: Pubkey) -> Result<()> {
+        let cpi_accounts = Burn {
+            mint: ctx.accounts.credit_mint.to_account_info(),
+            from: ctx.accounts.market_program_credit_token_account.to_account_info(),
+            authority: ctx.accounts.market_program_credit_token_account.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts
+        );
+        token::burn(cpi_ctx, amount)?;
+
+        emit!(CreditsBurned {
+            seq: ctx.accounts.state.next_event_seq(),
+            from: ctx.accounts.market_program_credit_token_account.key(),
+            job,
+            amount,
+        });
+        Ok(())
+    }
+
+    // Burns `amount` credits of market `job` and pays out the requested reserve's stablecoin at the
+    // tier rate of `provider`, returning the amount paid
+    pub fn redeem_and_burn(
+        ctx: Context<RedeemAndBurn>,
+        amount: u64,
+        job: Pubkey,
+        provider: Pubkey
+    ) -> Result<u64> {
         // require!(!ctx.accounts.state.paused, ErrorCode::ContractPaused);
 
-        let usdc_balance = ctx.accounts.program_usdc_token_account.amount;
-        require!(usdc_balance >= amount, ErrorCodes::NotEnoughUSDC);
+        // Providers without a tier assignment redeem at tier 0
+        let provider_tier = &ctx.accounts.provider_tier;
+        let tier = if provider_tier.owner == &crate::ID && !provider_tier.data_is_empty() {
+            ProviderTier::try_deserialize(&mut &provider_tier.try_borrow_data()?[..])?.tier
+        } else {
+            0
+        };
+        let redemption_bps = ctx.accounts.state.tier_redemption_bps[tier as usize] as u64;
+        let paid_amount = ((amount as u128) * (redemption_bps as u128) / (BPS_DENOMINATOR as u128)) as u64;
+        let haircut = amount - paid_amount;
+
+        // Stablecoins earmarked for subsidies are not available for redemption
+        let reserve = &mut ctx.accounts.reserve;
+        let usdc_balance = ctx.accounts.program_usdc_token_account.amount
+            .saturating_sub(reserve.subsidy_pool);
+        require!(usdc_balance >= paid_amount, ErrorCodes::NotEnoughUSDC);
+
+        reserve.total_redeemed += amount;
+        reserve.total_paid += paid_amount;
+        if ctx.accounts.state.haircut_to_subsidy {
+            reserve.subsidy_pool += haircut;
+        }
 
         // transfer usdc from the program to user
         let usdc_mint: Pubkey = ctx.accounts.usdc_mint.key();
@@ -85,13 +403,13 @@ pub mod oyster_credits {
             ctx.accounts.token_program.to_account_info(),
             cpi_accounts
         ).with_signer(signer_seeds);
-        token::transfer(cpi_ctx, amount)?;
+        token::transfer(cpi_ctx, paid_amount)?;
 
         // burn credits of the market program
         let cpi_accounts_burn = Burn {
             mint: ctx.accounts.credit_mint.to_account_info(),
             from: ctx.accounts.market_program_credit_token_account.to_account_info(),
-            authority: ctx.accounts.credit_mint.to_account_info(),
+            authority: ctx.accounts.market_program_credit_token_account.to_account_info(),
         };
 
         let cpi_ctx_burn = CpiContext::new(
@@ -100,7 +418,17 @@ pub mod oyster_credits {
         );
         token::burn(cpi_ctx_burn, amount)?;
 
-        Ok(())
+        emit!(CreditsRedeemed {
+            seq: ctx.accounts.state.next_event_seq(),
+            oyster_market: ctx.accounts.state.oyster_market,
+            job,
+            mint: usdc_mint,
+            provider,
+            to: ctx.accounts.user_usdc_token_account.owner,
+            face_amount: amount,
+            paid_amount,
+        });
+        Ok(paid_amount)
     }
 
     // pub fn emergency_withdraw(ctx: Context<EmergencyWithdraw>, amount: u64) -> Result<()> {
@@ -157,6 +485,49 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(minter: Pubkey)]
+pub struct AddMinter<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump
+    )]
+    pub state: Account<'info, State>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + MinterRole::INIT_SPACE,
+        seeds = [b"minter", minter.as_ref()],
+        bump
+    )]
+    pub minter_role: Account<'info, MinterRole>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeMinter<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump
+    )]
+    pub state: Account<'info, State>,
+
+    #[account(
+        mut,
+        seeds = [b"minter", minter_role.minter.as_ref()],
+        bump
+    )]
+    pub minter_role: Account<'info, MinterRole>,
+
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct MintTokens<'info> {
     #[account(
@@ -166,6 +537,13 @@ pub struct MintTokens<'info> {
     )]
     pub state: Account<'info, State>,
 
+    #[account(
+        mut,
+        seeds = [b"minter", signer.key().as_ref()],
+        bump
+    )]
+    pub minter_role: Account<'info, MinterRole>,
+
     #[account(
         mut,
         seeds = [b"credit_mint"],
@@ -202,6 +580,7 @@ pub struct BurnTokens<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(amount: u64, job: Pubkey, provider: Pubkey)]
 pub struct RedeemAndBurn<'info> {
     #[account(
         mut,
@@ -213,15 +592,19 @@ pub struct RedeemAndBurn<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
 
+    #[account(mut)]
+    pub usdc_mint: Account<'info, Mint>,
+
+    // Reserve backing redemptions in the mint the market pays out in
     #[account(
         mut,
-        constraint = usdc_mint.key() == state.usdc_mint
+        seeds = [b"reserve", usdc_mint.key().as_ref()],
+        bump
     )]
-    pub usdc_mint: Account<'info, Mint>,
+    pub reserve: Account<'info, Reserve>,
 
     #[account(
-        init_if_needed,
-        payer = signer,
+        mut,
         seeds = [b"program_usdc", usdc_mint.key().as_ref()],
         bump,
         token::mint = usdc_mint,
@@ -232,6 +615,13 @@ pub struct RedeemAndBurn<'info> {
     #[account(mut)]
     pub user_usdc_token_account: Account<'info, TokenAccount>,
 
+    /// CHECK: redemption tier of the provider being paid, tier 0 while not assigned
+    #[account(
+        seeds = [b"provider_tier", provider.as_ref()],
+        bump
+    )]
+    pub provider_tier: UncheckedAccount<'info>,
+
     #[account(
         mut,
         seeds = [b"credit_mint"],
@@ -255,10 +645,197 @@ pub struct RedeemAndBurn<'info> {
     // pub user_credit_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct BurnMarketCredits<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump
+    )]
+    pub state: Account<'info, State>,
+
+    #[account(
+        mut,
+        seeds = [b"credit_mint"],
+        bump
+    )]
+    pub credit_mint: Account<'info, Mint>,
 
+    // Signed by the market program
+    #[account(
+        mut,
+        seeds = [b"credit_token", credit_mint.key().as_ref()],
+        bump,
+        token::mint = credit_mint,
+        token::authority = market_program_credit_token_account,
+        seeds::program = state.oyster_market
+    )]
+    pub market_program_credit_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(provider: Pubkey)]
+pub struct SetProviderTier<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump
+    )]
+    pub state: Account<'info, State>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ProviderTier::INIT_SPACE,
+        seeds = [b"provider_tier", provider.as_ref()],
+        bump
+    )]
+    pub provider_tier: Account<'info, ProviderTier>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct WithdrawSubsidy<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump
+    )]
+    pub state: Account<'info, State>,
+
+    pub usdc_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"reserve", usdc_mint.key().as_ref()],
+        bump
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    #[account(
+        mut,
+        seeds = [b"program_usdc", usdc_mint.key().as_ref()],
+        bump
+    )]
+    pub program_usdc_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub receiver: Account<'info, TokenAccount>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawReserve<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump
+    )]
+    pub state: Account<'info, State>,
+
+    pub usdc_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"reserve", usdc_mint.key().as_ref()],
+        bump
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    #[account(
+        mut,
+        seeds = [b"program_usdc", usdc_mint.key().as_ref()],
+        bump
+    )]
+    pub program_usdc_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub receiver: Account<'info, TokenAccount>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct AddReserve<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump
+    )]
+    pub state: Account<'info, State>,
+
+    pub usdc_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"credit_mint"],
+        bump
+    )]
+    pub credit_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Reserve::INIT_SPACE,
+        seeds = [b"reserve", usdc_mint.key().as_ref()],
+        bump
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [b"program_usdc", usdc_mint.key().as_ref()],
+        bump,
+        token::mint = usdc_mint,
+        token::authority = program_usdc_token_account
+    )]
+    pub program_usdc_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositReserve<'info> {
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump
+    )]
+    pub state: Account<'info, State>,
+
+    pub usdc_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"reserve", usdc_mint.key().as_ref()],
+        bump
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    #[account(
+        mut,
+        seeds = [b"program_usdc", usdc_mint.key().as_ref()],
+        bump
+    )]
+    pub program_usdc_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+    pub depositor: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
 #[derive(Accounts)]
 pub struct EmergencyWithdraw<'info> {
     #[account(mut)]
@@ -274,18 +851,172 @@ pub struct EmergencyWithdraw<'info> {
 
 #[derive(Accounts)]
 pub struct AdminAction<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"state"],
+        bump
+    )]
     pub state: Account<'info, State>,
     pub authority: Signer<'info>,
 }
 
+// Reserves replaced the single USDC mint of the first deployment and its state is not migrated,
+// so upgrading needs a fresh deployment rather than an in-place program upgrade
 #[account]
 #[derive(InitSpace)]
 pub struct State {
     pub admin: Pubkey,
     pub oyster_market: Pubkey,
-    pub usdc_mint: Pubkey,
     pub initialized: bool,
+    pub tier_redemption_bps: [u16; MAX_TIERS],
+    pub haircut_to_subsidy: bool,
+    // Incremented on every event so indexers can detect gaps
+    pub event_seq: u64,
+}
+
+impl State {
+    pub fn next_event_seq(&mut self) -> u64 {
+        self.event_seq += 1;
+        self.event_seq
+    }
+}
+
+// Per-mint reserve backing credit redemptions
+#[account]
+#[derive(InitSpace)]
+pub struct Reserve {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub total_deposited: u64,
+    pub total_redeemed: u64, // Face value of credits redeemed against this reserve
+    pub total_paid: u64,
+    pub subsidy_pool: u64,
+    pub total_withdrawn: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MinterRole {
+    pub minter: Pubkey,
+    pub window_allowance: u64,
+    pub window_duration: u64,
+    pub window_start: u64,
+    pub window_minted: u64,
+    pub lifetime_cap: u64,
+    pub lifetime_minted: u64,
+    pub revoked: bool,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProviderTier {
+    pub provider: Pubkey,
+    pub tier: u8,
+}
+
+#[event]
+pub struct CreditsInitialized {
+    pub seq: u64,
+    pub admin: Pubkey,
+    pub oyster_market: Pubkey,
+}
+
+#[event]
+pub struct ReserveAdded {
+    pub seq: u64,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct ReserveDeposited {
+    pub seq: u64,
+    pub mint: Pubkey,
+    pub from: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CreditsMinted {
+    pub seq: u64,
+    pub minter: Pubkey,
+    pub role: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct MinterAdded {
+    pub seq: u64,
+    pub minter: Pubkey,
+    pub role: Pubkey,
+    pub window_allowance: u64,
+    pub window_duration: u64,
+    pub lifetime_cap: u64,
+}
+
+#[event]
+pub struct MinterRevoked {
+    pub seq: u64,
+    pub minter: Pubkey,
+    pub role: Pubkey,
+}
+
+#[event]
+pub struct CreditsBurned {
+    pub seq: u64,
+    pub from: Pubkey,
+    // Market job the credits were refunded from, default for admin burns
+    pub job: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CreditsRedeemed {
+    pub seq: u64,
+    pub oyster_market: Pubkey,
+    pub job: Pubkey,
+    pub mint: Pubkey,
+    pub provider: Pubkey,
+    pub to: Pubkey,
+    pub face_amount: u64,
+    pub paid_amount: u64,
+}
+
+#[event]
+pub struct TierRedemptionRateUpdated {
+    pub seq: u64,
+    pub tier: u8,
+    pub redemption_bps: u16,
+}
+
+#[event]
+pub struct ProviderTierUpdated {
+    pub seq: u64,
+    pub provider: Pubkey,
+    pub tier: u8,
+}
+
+#[event]
+pub struct HaircutDestinationUpdated {
+    pub seq: u64,
+    pub haircut_to_subsidy: bool,
+}
+
+#[event]
+pub struct SubsidyWithdrawn {
+    pub seq: u64,
+    pub mint: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReserveWithdrawn {
+    pub seq: u64,
+    pub mint: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
 }
 
 #[error_code]
@@ -298,4 +1029,22 @@ pub enum ErrorCodes {
     ContractPaused,
     #[msg("Not enough USDC balance.")]
     NotEnoughUSDC,
+    #[msg("Invalid amount.")]
+    InvalidAmount,
+    #[msg("Invalid provider tier.")]
+    InvalidTier,
+    #[msg("Redemption rate cannot exceed face value.")]
+    InvalidRedemptionRate,
+    #[msg("Not enough USDC in the subsidy pool.")]
+    NotEnoughSubsidy,
+    #[msg("Mint window duration must be positive.")]
+    InvalidMintWindow,
+    #[msg("Minter role has been revoked.")]
+    MinterRevoked,
+    #[msg("Mint exceeds the minter's window allowance.")]
+    MintAllowanceExceeded,
+    #[msg("Mint exceeds the minter's lifetime cap.")]
+    MintCapExceeded,
+    #[msg("Reserve mint decimals must match the credit mint.")]
+    ReserveDecimalsMismatch,
 }
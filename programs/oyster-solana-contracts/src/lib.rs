@@ -1,12 +1,43 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer, Mint};
-use oyster_credits::{cpi::accounts::RedeemAndBurn, program::OysterCredits};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, spl_token::instruction::AuthorityType, Burn, FreezeAccount, Mint, MintTo, SetAuthority, Token, TokenAccount, Transfer};
+use oyster_credits::{cpi::accounts::{BurnMarketCredits, RedeemAndBurn}, program::OysterCredits};
 
 declare_id!("5dk2pVaDQoUVK2tuNwQhoJHupwFd3q8iqZPkieMiwKoJ");
 
 // Define EXTRA_DECIMALS as a constant
 const EXTRA_DECIMALS: u64 = 12; // Equivalent to 10^12
 
+// Provider operator permissions
+pub const OPERATOR_UPDATE_CP: u8 = 1 << 0;   // Update the control plane URL
+// 1 << 1 is unused, anyone can settle jobs
+pub const OPERATOR_ACCEPT_JOBS: u8 = 1 << 2; // Accept jobs
+const OPERATOR_ALL: u8 = OPERATOR_UPDATE_CP | OPERATOR_ACCEPT_JOBS;
+
+// Maximum number of payout beneficiaries per provider
+const MAX_BENEFICIARIES: usize = 5;
+// Payout shares are expressed in basis points
+const BPS_DENOMINATOR: u16 = 10_000;
+
+// Provider profile limits
+const MAX_PROFILE_ENTRIES: usize = 16;     // Per list (control planes, regions, instance types)
+const MAX_PROFILE_ENTRY_LEN: usize = 100;
+const MAX_METADATA_URI_LEN: usize = 200;
+
+// Time a provider has to accept a new job until the admin changes it (1 day)
+const DEFAULT_ACCEPT_TIMEOUT: u64 = 24 * 60 * 60;
+
+// Outstanding sponsorships per job, so closing can refund them all in one transaction
+const MAX_JOB_SPONSORS: u64 = 8;
+
+// Length of an auto top-up cap period (30 days)
+const TOPUP_PERIOD: u64 = 30 * 24 * 60 * 60;
+
+// Rate card limits
+const MAX_RATE_CARD_ENTRIES: usize = 16;
+const MAX_RATE_KEY_LEN: usize = 32;
+
 #[program]
 pub mod market_v {
     use super::*;
@@ -34,12 +65,27 @@ pub mod market_v {
         market.notice_period = notice_period;
         market.oyster_credit = oyster_credit;
         market.credit_mint = credit_mint;
+        market.credit_refund_policy = CreditRefundPolicy::User;
+        market.accept_timeout = DEFAULT_ACCEPT_TIMEOUT;
 
         Ok(())
     }
 
-    // Add a provider
-    pub fn provider_add(ctx: Context<ProviderAdd>, cp: String) -> Result<()> {
+    // Add a provider, posting its initial bond in the market token
+    pub fn provider_add(ctx: Context<ProviderAdd>, cp: String, bond: u64) -> Result<()> {
+        require!(bond >= ctx.accounts.market.min_provider_bond, ErrorCodes::InsufficientProviderBond);
+        if bond > 0 {
+            let authority_token_account = ctx.accounts.authority_token_account.as_ref()
+                .ok_or(ErrorCodes::BondAccountRequired)?;
+            let cpi_accounts = Transfer {
+                from: authority_token_account.to_account_info(),
+                to: ctx.accounts.bond_token_account.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            token::transfer(cpi_ctx, bond)?;
+        }
+
         let provider = &mut ctx.accounts.provider;
 
         // Check 1: Ensure the provider does not already exist
@@ -48,42 +94,136 @@ pub mod market_v {
         // Check 2: Ensure the control plane URL is not empty
         require!(!cp.is_empty(), ErrorCodes::InvalidControlPlaneUrl);
 
-        // Set the control plane URL and authority
+        // Check 3: In a permissioned market the provider needs an approved application for the
+        // reviewed control plane URL
+        if ctx.accounts.market.permissioned {
+            let application = ctx.accounts.application.as_ref().ok_or(ErrorCodes::ProviderNotApproved)?;
+            require!(application.approved, ErrorCodes::ProviderNotApproved);
+            require!(application.cp == cp, ErrorCodes::InvalidControlPlaneUrl);
+        }
+
+        // The application is consumed on registration, returning its rent to the provider
+        if let Some(application) = &ctx.accounts.application {
+            application.close(ctx.accounts.authority.to_account_info())?;
+        }
+
+        // Set the control plane URL and authority. The registering key stays the provider's
+        // id, which jobs reference, even after the authority is rotated.
         provider.cp = cp;
+        provider.id = *ctx.accounts.authority.key;
         provider.owner = *ctx.accounts.authority.key;
+        provider.status = ProviderStatus::Active;
+        provider.bond = bond;
+        provider.bond_mint = ctx.accounts.token_mint.key();
 
         emit!(ProviderAdded {
             provider: *ctx.accounts.authority.key,
             cp: provider.cp.clone(),
         });
 
+        if bond > 0 {
+            emit!(ProviderBonded {
+                provider: provider.id,
+                amount: bond,
+                bond,
+            });
+        }
+
         Ok(())
     }
 
-    // Remove a provider
+    // Remove a provider. The provider winds down: new jobs are refused while running jobs can still
+    // be closed, and the account can be closed once no active jobs remain.
     pub fn provider_remove(ctx: Context<ProviderRemove>) -> Result<()> {
-        // Ensure the caller is the provider's authority
-        let authority = ctx.accounts.authority.key();
-        let (expected_provider_key, _) =
-            Pubkey::find_program_address(&[b"provider", authority.as_ref()], ctx.program_id);
+        let provider = &mut ctx.accounts.provider;
+        require!(provider.status == ProviderStatus::Active, ErrorCodes::ProviderNotActive);
+        provider.status = ProviderStatus::WindingDown;
 
-        require!(
-            ctx.accounts.provider.key() == expected_provider_key,
-            ErrorCodes::Unauthorized
-        );
+        emit!(ProviderRemoved {
+            provider: provider.id,
+            active_jobs: provider.active_jobs,
+        });
 
-        // // Close the provider account
-        // let provider_account = ctx.accounts.provider.to_account_info();
-        // let authority = ctx.accounts.authority.to_account_info();
+        Ok(())
+    }
 
-        // // Refund the rent to the authority
-        // let rent = Rent::get()?;
-        // let lamports = provider_account.lamports();
-        // **provider_account.lamports.borrow_mut() = 0;
-        // **authority.lamports.borrow_mut() += lamports;
+    // Close a removed provider's account once all of its jobs are closed
+    pub fn provider_close(ctx: Context<ProviderClose>) -> Result<()> {
+        let provider = &ctx.accounts.provider;
+        require!(provider.status == ProviderStatus::WindingDown, ErrorCodes::ProviderNotWindingDown);
+        require!(provider.active_jobs == 0, ErrorCodes::ProviderHasActiveJobs);
+        require!(provider.bond == 0 && provider.unbonding_amount == 0, ErrorCodes::ProviderStillBonded);
 
-        emit!(ProviderRemoved {
-            provider: *ctx.accounts.authority.key,
+        let authority = ctx.accounts.authority.to_account_info();
+        utils_mod::close_if_exists(&ctx.accounts.profile, &authority)?;
+        utils_mod::close_if_exists(&ctx.accounts.rate_card, &authority)?;
+
+        emit!(ProviderClosed {
+            provider: provider.id,
+        });
+
+        Ok(())
+    }
+
+    // Apply to register as a provider in a permissioned market
+    pub fn provider_apply(ctx: Context<ProviderApply>, cp: String) -> Result<()> {
+        require!(!cp.is_empty(), ErrorCodes::InvalidControlPlaneUrl);
+
+        let application = &mut ctx.accounts.application;
+        application.applicant = *ctx.accounts.applicant.key;
+        application.cp = cp;
+        application.approved = false;
+
+        emit!(ProviderApplied {
+            applicant: application.applicant,
+            cp: application.cp.clone(),
+        });
+
+        Ok(())
+    }
+
+    // Approve a provider application
+    pub fn provider_application_approve(ctx: Context<ProviderApplicationApprove>) -> Result<()> {
+        let application = &mut ctx.accounts.application;
+        application.approved = true;
+
+        emit!(ProviderApplicationApproved {
+            applicant: application.applicant,
+        });
+
+        Ok(())
+    }
+
+    // Reject a provider application, returning its rent to the applicant
+    pub fn provider_application_reject(ctx: Context<ProviderApplicationReject>) -> Result<()> {
+        emit!(ProviderApplicationRejected {
+            applicant: ctx.accounts.application.applicant,
+        });
+
+        Ok(())
+    }
+
+    // Suspend a provider. Running jobs are unaffected, but no new jobs can be opened.
+    pub fn provider_suspend(ctx: Context<ProviderAdminAction>) -> Result<()> {
+        let provider = &mut ctx.accounts.provider;
+        require!(!provider.suspended, ErrorCodes::ProviderSuspended);
+        provider.suspended = true;
+
+        emit!(ProviderSuspended {
+            provider: provider.id,
+        });
+
+        Ok(())
+    }
+
+    // Lift a provider suspension
+    pub fn provider_reinstate(ctx: Context<ProviderAdminAction>) -> Result<()> {
+        let provider = &mut ctx.accounts.provider;
+        require!(provider.suspended, ErrorCodes::ProviderNotSuspended);
+        provider.suspended = false;
+
+        emit!(ProviderReinstated {
+            provider: provider.id,
         });
 
         Ok(())
@@ -94,6 +234,13 @@ pub mod market_v {
         ctx: Context<ProviderUpdateWithCp>,
         new_cp: String,
     ) -> Result<()> {
+        utils_mod::require_provider_permission(
+            &ctx.accounts.provider,
+            ctx.accounts.operator.as_deref(),
+            ctx.accounts.authority.key,
+            OPERATOR_UPDATE_CP,
+        )?;
+
         let provider = &mut ctx.accounts.provider;
 
         // Check 1: Ensure the provider exists
@@ -106,10 +253,389 @@ pub mod market_v {
         provider.cp = new_cp;
 
         emit!(ProviderUpdatedWithCp {
-            provider: *ctx.accounts.authority.key,
+            provider: provider.id,
             new_cp: provider.cp.clone(),
         });
 
+        // The profile lists the control plane URL as its primary endpoint
+        let profile_info = ctx.accounts.profile.to_account_info();
+        if profile_info.owner == &crate::ID && !profile_info.data_is_empty() {
+            let mut profile = ProviderProfile::try_deserialize(&mut &profile_info.try_borrow_data()?[..])?;
+            profile.cps = profile.cps_with_primary(&provider.cp);
+            profile.version += 1;
+
+            let space = ProviderProfile::space(
+                &profile.cps,
+                &profile.regions,
+                &profile.instance_types,
+                &profile.metadata_uri,
+            );
+            utils_mod::resize_account(
+                &profile_info,
+                space,
+                &ctx.accounts.authority,
+                &ctx.accounts.system_program,
+            )?;
+            profile.try_serialize(&mut &mut profile_info.try_borrow_mut_data()?[..])?;
+
+            emit!(ProviderProfileUpdated {
+                provider: provider.id,
+                version: profile.version,
+                cps: Some(profile.cps.clone()),
+                regions: None,
+                instance_types: None,
+                attestation_key: None,
+                metadata_uri: None,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Create a provider's profile, seeded with its control plane URL
+    pub fn provider_profile_init(ctx: Context<ProviderProfileInit>) -> Result<()> {
+        let provider = &ctx.accounts.provider;
+        let profile = &mut ctx.accounts.profile;
+        profile.provider = provider.key();
+        profile.version = 0;
+        profile.cps = vec![provider.cp.clone()];
+
+        emit!(ProviderProfileUpdated {
+            provider: provider.id,
+            version: profile.version,
+            cps: Some(profile.cps.clone()),
+            regions: None,
+            instance_types: None,
+            attestation_key: None,
+            metadata_uri: None,
+        });
+
+        Ok(())
+    }
+
+    // Update fields of a provider's profile, resizing the account as needed. Only changed fields
+    // are reported in the event.
+    pub fn provider_profile_update(
+        ctx: Context<ProviderProfileSet>,
+        update: ProfileUpdate,
+    ) -> Result<()> {
+        update.validate()?;
+
+        let profile = &mut ctx.accounts.profile;
+        let cps = update.cps.filter(|cps| *cps != profile.cps);
+        let regions = update.regions.filter(|regions| *regions != profile.regions);
+        let instance_types = update.instance_types.filter(|types| *types != profile.instance_types);
+        let attestation_key = update.attestation_key.filter(|key| *key != profile.attestation_key);
+        let metadata_uri = update.metadata_uri.filter(|uri| *uri != profile.metadata_uri);
+
+        // Operators cannot rotate the enclave attestation key
+        if attestation_key.is_some() {
+            require_keys_eq!(
+                ctx.accounts.authority.key(),
+                ctx.accounts.provider.owner,
+                ErrorCodes::Unauthorized
+            );
+        }
+
+        if let Some(cps) = &cps {
+            profile.cps = cps.clone();
+
+            // The first endpoint is the provider's control plane URL
+            let provider = &mut ctx.accounts.provider;
+            if provider.cp != cps[0] {
+                provider.cp = cps[0].clone();
+
+                emit!(ProviderUpdatedWithCp {
+                    provider: provider.id,
+                    new_cp: provider.cp.clone(),
+                });
+            }
+        }
+        if let Some(regions) = &regions {
+            profile.regions = regions.clone();
+        }
+        if let Some(instance_types) = &instance_types {
+            profile.instance_types = instance_types.clone();
+        }
+        if let Some(attestation_key) = attestation_key {
+            profile.attestation_key = attestation_key;
+        }
+        if let Some(metadata_uri) = &metadata_uri {
+            profile.metadata_uri = metadata_uri.clone();
+        }
+        profile.version += 1;
+
+        emit!(ProviderProfileUpdated {
+            provider: ctx.accounts.provider.id,
+            version: profile.version,
+            cps,
+            regions,
+            instance_types,
+            attestation_key,
+            metadata_uri,
+        });
+
+        Ok(())
+    }
+
+    // Publish a provider's rate card. The first card applies immediately, later changes take effect
+    // after the market notice period so that owners can react before new jobs are held to them.
+    pub fn provider_rate_card_set(
+        ctx: Context<ProviderRateCardSet>,
+        entries: Vec<RateCardEntry>,
+    ) -> Result<()> {
+        require!(entries.len() <= MAX_RATE_CARD_ENTRIES, ErrorCodes::InvalidRateCard);
+        require!(
+            entries.iter().all(|entry| !entry.instance_type.is_empty()
+                && entry.instance_type.len() <= MAX_RATE_KEY_LEN
+                && entry.region.len() <= MAX_RATE_KEY_LEN),
+            ErrorCodes::InvalidRateCard
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let rate_card = &mut ctx.accounts.rate_card;
+        rate_card.provider = ctx.accounts.provider.key();
+
+        let effective_at = if ctx.accounts.provider.has_rate_card {
+            // Promote a pending change that already took effect before replacing it
+            rate_card.apply_pending(now);
+            rate_card.pending_entries = entries.clone();
+            rate_card.effective_at = now + ctx.accounts.market.notice_period as i64;
+            rate_card.effective_at
+        } else {
+            // Nothing is enforced yet, so there is no previous card to give notice on
+            rate_card.entries = entries.clone();
+            rate_card.pending_entries = Vec::new();
+            rate_card.effective_at = 0;
+            now
+        };
+
+        ctx.accounts.provider.has_rate_card = true;
+
+        emit!(ProviderRateCardUpdated {
+            provider: ctx.accounts.provider.id,
+            entries,
+            effective_at,
+        });
+
+        Ok(())
+    }
+
+    // Direct a provider's earnings to beneficiaries by basis-point share. An empty list pays the
+    // provider's own token account.
+    pub fn provider_set_payout(
+        ctx: Context<ProviderSetPayout>,
+        beneficiaries: Vec<Beneficiary>,
+    ) -> Result<()> {
+        require!(beneficiaries.len() <= MAX_BENEFICIARIES, ErrorCodes::InvalidPayoutConfig);
+
+        if !beneficiaries.is_empty() {
+            let mut total_bps = 0u32;
+            for beneficiary in beneficiaries.iter() {
+                require!(
+                    beneficiary.wallet != Pubkey::default() && beneficiary.share_bps > 0,
+                    ErrorCodes::InvalidPayoutConfig
+                );
+                total_bps += beneficiary.share_bps as u32;
+            }
+            require!(total_bps == BPS_DENOMINATOR as u32, ErrorCodes::InvalidPayoutConfig);
+        }
+
+        let provider = &mut ctx.accounts.provider;
+        provider.beneficiaries = beneficiaries;
+
+        emit!(ProviderPayoutUpdated {
+            provider: provider.id,
+            beneficiaries: provider.beneficiaries.clone(),
+        });
+
+        Ok(())
+    }
+
+    // Add to a provider's bond
+    pub fn provider_bond_deposit(ctx: Context<ProviderBondDeposit>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCodes::InvalidAmount);
+
+        // A bond left in a previous market token must be withdrawn before bonding in the new one
+        let provider = &mut ctx.accounts.provider;
+        if provider.bond_mint != ctx.accounts.token_mint.key() {
+            require!(provider.bond == 0 && provider.unbonding_amount == 0, ErrorCodes::ProviderStillBonded);
+            provider.bond_mint = ctx.accounts.token_mint.key();
+        }
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.authority_token_account.to_account_info(),
+            to: ctx.accounts.bond_token_account.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        let provider = &mut ctx.accounts.provider;
+        provider.bond += amount;
+
+        emit!(ProviderBonded {
+            provider: provider.id,
+            amount,
+            bond: provider.bond,
+        });
+
+        Ok(())
+    }
+
+    // Start unbonding part of a provider's bond. Requesting again adds to the unbonding amount
+    // and restarts the unbonding period.
+    pub fn provider_unbond_request(ctx: Context<ProviderUnbondRequest>, amount: u64) -> Result<()> {
+        let provider = &mut ctx.accounts.provider;
+        require!(amount > 0 && amount <= provider.bond, ErrorCodes::InvalidAmount);
+
+        provider.bond -= amount;
+        provider.unbonding_amount += amount;
+        provider.unbond_at = Clock::get()?.unix_timestamp + ctx.accounts.market.unbonding_period as i64;
+
+        emit!(ProviderUnbondRequested {
+            provider: provider.id,
+            amount: provider.unbonding_amount,
+            unbond_at: provider.unbond_at,
+        });
+
+        Ok(())
+    }
+
+    // Withdraw an unbonded amount once the unbonding period is over
+    pub fn provider_unbond_withdraw(ctx: Context<ProviderUnbondWithdraw>) -> Result<()> {
+        let amount = ctx.accounts.provider.unbonding_amount;
+        require!(amount > 0, ErrorCodes::NothingToUnbond);
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.provider.unbond_at,
+            ErrorCodes::UnbondingPeriodNotOver
+        );
+
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let seeds: &[&[u8]] = &[b"provider_bond", token_mint_key.as_ref(), &[ctx.bumps.bond_token_account]];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.bond_token_account.to_account_info(),
+            to: ctx.accounts.authority_token_account.to_account_info(),
+            authority: ctx.accounts.bond_token_account.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        token::transfer(cpi_ctx, amount)?;
+
+        let provider = &mut ctx.accounts.provider;
+        provider.unbonding_amount = 0;
+        provider.unbond_at = 0;
+
+        emit!(ProviderUnbonded {
+            provider: provider.id,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Slash a provider's bond to refund the owner of an affected job. Amounts still unbonding are
+    // slashed once the bond is exhausted.
+    pub fn provider_slash(ctx: Context<ProviderSlash>, _job_index: u128, amount: u64) -> Result<()> {
+        let owner = utils_mod::job_owner(&ctx.accounts.job, ctx.accounts.job_nft_account.as_deref())?;
+        require!(ctx.accounts.owner_token_account.owner == owner, ErrorCodes::Unauthorized);
+
+        let provider = &mut ctx.accounts.provider_account;
+        require!(
+            amount > 0 && amount <= provider.bond + provider.unbonding_amount,
+            ErrorCodes::InvalidAmount
+        );
+
+        let from_bond = amount.min(provider.bond);
+        provider.bond -= from_bond;
+        provider.unbonding_amount -= amount - from_bond;
+
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let seeds: &[&[u8]] = &[b"provider_bond", token_mint_key.as_ref(), &[ctx.bumps.bond_token_account]];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.bond_token_account.to_account_info(),
+            to: ctx.accounts.owner_token_account.to_account_info(),
+            authority: ctx.accounts.bond_token_account.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        token::transfer(cpi_ctx, amount)?;
+
+        emit!(ProviderSlashed {
+            provider: ctx.accounts.provider_account.id,
+            job: ctx.accounts.job.key(),
+            to: owner,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Propose a new authority key for a provider
+    pub fn provider_rotate_key(ctx: Context<ProviderRotateKey>, new_owner: Pubkey) -> Result<()> {
+        let provider = &mut ctx.accounts.provider;
+        require!(new_owner != provider.owner, ErrorCodes::InvalidProviderKey);
+        provider.pending_owner = new_owner;
+
+        emit!(ProviderKeyRotationStarted {
+            provider: provider.id,
+            new_owner,
+        });
+
+        Ok(())
+    }
+
+    // Accept a proposed provider authority. Jobs keep referencing the provider id, so running
+    // jobs and their payouts follow the new key.
+    pub fn provider_accept_key(ctx: Context<ProviderAcceptKey>) -> Result<()> {
+        let provider = &mut ctx.accounts.provider;
+        let old_owner = provider.owner;
+        provider.owner = provider.pending_owner;
+        provider.pending_owner = Pubkey::default();
+
+        emit!(ProviderKeyRotated {
+            provider: provider.id,
+            old_owner,
+            new_owner: provider.owner,
+        });
+
+        Ok(())
+    }
+
+    // Register an operator key, or update its permissions
+    pub fn provider_operator_set(
+        ctx: Context<ProviderOperatorSet>,
+        operator: Pubkey,
+        permissions: u8,
+    ) -> Result<()> {
+        require!(
+            permissions != 0 && permissions & !OPERATOR_ALL == 0,
+            ErrorCodes::InvalidOperatorPermissions
+        );
+
+        let provider_operator = &mut ctx.accounts.provider_operator;
+        provider_operator.provider = ctx.accounts.provider.key();
+        provider_operator.operator = operator;
+        provider_operator.permissions = permissions;
+
+        emit!(ProviderOperatorUpdated {
+            provider: ctx.accounts.provider.id,
+            operator,
+            permissions,
+        });
+
+        Ok(())
+    }
+
+    // Remove an operator key
+    pub fn provider_operator_remove(ctx: Context<ProviderOperatorRemove>) -> Result<()> {
+        emit!(ProviderOperatorRemoved {
+            provider: ctx.accounts.provider.id,
+            operator: ctx.accounts.provider_operator.operator,
+        });
+
         Ok(())
     }
 
@@ -133,6 +659,104 @@ pub mod market_v {
         Ok(())
     }
 
+    // Update where unused credits go on job withdraw and close
+    pub fn update_credit_refund_policy(
+        ctx: Context<UpdateMarket>,
+        policy: CreditRefundPolicy,
+        credit_issuer: Pubkey,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        // Returning credits to the issuer needs a known issuer
+        require!(
+            policy != CreditRefundPolicy::Issuer || credit_issuer != Pubkey::default(),
+            ErrorCodes::InvalidCreditIssuer
+        );
+
+        market.credit_refund_policy = policy;
+        market.credit_issuer = credit_issuer;
+
+        emit!(CreditRefundPolicyUpdated {
+            policy,
+            credit_issuer,
+        });
+
+        Ok(())
+    }
+
+    // Switch provider registration between open and admin-approved
+    pub fn update_permissioned(ctx: Context<UpdateMarket>, permissioned: bool) -> Result<()> {
+        ctx.accounts.market.permissioned = permissioned;
+
+        emit!(MarketPermissionedUpdated {
+            permissioned,
+        });
+
+        Ok(())
+    }
+
+    // Update the smallest deposit accepted from a job sponsor
+    pub fn update_min_sponsor_deposit(ctx: Context<UpdateMarket>, min_sponsor_deposit: u64) -> Result<()> {
+        ctx.accounts.market.min_sponsor_deposit = min_sponsor_deposit;
+
+        emit!(MinSponsorDepositUpdated {
+            min_sponsor_deposit,
+        });
+
+        Ok(())
+    }
+
+    // Update the bounty paid for reaping exhausted jobs
+    pub fn update_reap_bounty(ctx: Context<UpdateMarket>, reap_bounty: u64) -> Result<()> {
+        ctx.accounts.market.reap_bounty = reap_bounty;
+
+        emit!(ReapBountyUpdated {
+            reap_bounty,
+        });
+
+        Ok(())
+    }
+
+    // Keep tombstones of closed jobs instead of deleting their accounts
+    pub fn update_tombstone_jobs(ctx: Context<UpdateMarket>, tombstone_jobs: bool) -> Result<()> {
+        ctx.accounts.market.tombstone_jobs = tombstone_jobs;
+
+        emit!(TombstoneJobsUpdated {
+            tombstone_jobs,
+        });
+
+        Ok(())
+    }
+
+    // Update the time providers have to accept new jobs
+    pub fn update_accept_timeout(ctx: Context<UpdateMarket>, accept_timeout: u64) -> Result<()> {
+        ctx.accounts.market.accept_timeout = accept_timeout;
+
+        emit!(AcceptTimeoutUpdated {
+            accept_timeout,
+        });
+
+        Ok(())
+    }
+
+    // Update the provider bond requirement and unbonding period
+    pub fn update_bond_config(
+        ctx: Context<UpdateMarket>,
+        min_provider_bond: u64,
+        unbonding_period: u64,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        market.min_provider_bond = min_provider_bond;
+        market.unbonding_period = unbonding_period;
+
+        emit!(BondConfigUpdated {
+            min_provider_bond,
+            unbonding_period,
+        });
+
+        Ok(())
+    }
+
     // Open a new job
     // #[inline(never)] // needed due to stack size violation
     pub fn job_open(
@@ -147,14 +771,44 @@ pub mod market_v {
 
         // require_keys_eq!(ctx.accounts.token_mint.key(), market.token_mint, ErrorCodes::InvalidMint);
 
+        // Providers winding down do not take new jobs
+        let provider_account = &mut ctx.accounts.provider_account;
+        require!(provider_account.status == ProviderStatus::Active, ErrorCodes::ProviderNotActive);
+        require!(!provider_account.suspended, ErrorCodes::ProviderSuspended);
+        // Only providers bonded in the market token take new jobs
+        let bond = if provider_account.bond_mint == market.token_mint { provider_account.bond } else { 0 };
+        require!(bond >= market.min_provider_bond, ErrorCodes::InsufficientProviderBond);
+        provider_account.active_jobs += 1;
+
+        let now = Clock::get()?.unix_timestamp;
+        utils_mod::require_rate_card(
+            &ctx.accounts.provider_account,
+            ctx.accounts.rate_card.as_deref(),
+            &metadata,
+            rate,
+            now,
+        )?;
+
         // Initialize the job
         job.index = market.job_index;
         job.metadata = metadata; // Now a String
         job.owner = *ctx.accounts.owner.key;
+        job.nft_mint = ctx.accounts.job_nft_mint.as_ref().map_or(Pubkey::default(), |mint| mint.key());
         job.provider = provider;
-        // job.rate = rate;
+        // Record the provider's control plane at open so orphaned jobs can be told apart
+        job.provider_cp = ctx.accounts.provider_account.cp.clone();
         job.balance = balance;
-        job.last_settled = Clock::get()?.unix_timestamp as u64;
+        job.last_settled = now as u64;
+
+        // Billing starts once the provider accepts the job
+        require!(rate > 0, ErrorCodes::InvalidRate);
+        job.rate = rate;
+        job.status = JobStatus::Pending;
+        job.accept_deadline = if market.accept_timeout > 0 {
+            now as u64 + market.accept_timeout
+        } else {
+            0
+        };
 
         // Increment the job index
         market.job_index += 1;
@@ -167,48 +821,66 @@ pub mod market_v {
             &mut ctx.accounts.token_mint,
             &mut ctx.accounts.user_token_account,
             &mut ctx.accounts.program_token_account,
-            &ctx.accounts.owner,
+            &ctx.accounts.owner.to_account_info(),
+            &[],
             &ctx.accounts.token_program,
             // job_index,
             balance
         )?;
 
-        let token_mint_key = ctx.accounts.token_mint.key();
-        let seeds: &[&[u8]] = &[b"job_token", token_mint_key.as_ref(), &[ctx.bumps.program_token_account]];
-        let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
+        emit!(JobOpened {
+            job: job.key(),
+            metadata: job.metadata.clone(), // Cloning the String
+            owner: job.owner,
+            provider: job.provider,
+            provider_cp: job.provider_cp.clone(),
+            rate: job.rate,
+            balance: job.balance,
+            accept_deadline: job.accept_deadline,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
-        utils_mod::job_revise_rate_internal(
-            job,
-            &ctx.accounts.token_mint,
-            &mut ctx.accounts.program_token_account,
-            &mut ctx.accounts.provider_token_account,
-            &ctx.accounts.credit_mint,
-            &mut ctx.accounts.program_credit_token_account,
-            &ctx.accounts.token_program,
-            signer_seeds,
-            rate,
-            market.notice_period,
-            &ctx.accounts.owner,
-            &mut ctx.accounts.state,
-            &mut ctx.accounts.credit_program_usdc_token_account,
-            &ctx.accounts.credit_program,
-        )?;
-
-        emit!(JobOpened {
-            job: job.key(),
-            metadata: job.metadata.clone(), // Cloning the String
-            owner: job.owner,
-            provider: job.provider,
-            rate: job.rate,
-            balance: job.balance,
-            timestamp: Clock::get()?.unix_timestamp,
-        });
+        // Tokenized jobs are owned by whoever holds their NFT
+        match (&ctx.accounts.job_nft_mint, &ctx.accounts.owner_nft_account) {
+            (Some(job_nft_mint), Some(owner_nft_account)) => {
+                let job_index = ctx.accounts.job.index.to_le_bytes();
+                let seeds: &[&[u8]] = &[b"job", job_index.as_ref(), &[ctx.bumps.job]];
+                let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+                let cpi_accounts = MintTo {
+                    mint: job_nft_mint.to_account_info(),
+                    to: owner_nft_account.to_account_info(),
+                    authority: ctx.accounts.job.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+                    .with_signer(signer_seeds);
+                token::mint_to(cpi_ctx, 1)?;
+
+                // Drop the mint authority so the supply stays at one
+                let cpi_accounts = SetAuthority {
+                    current_authority: ctx.accounts.job.to_account_info(),
+                    account_or_mint: job_nft_mint.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+                    .with_signer(signer_seeds);
+                token::set_authority(cpi_ctx, AuthorityType::MintTokens, None)?;
+
+                emit!(JobTokenized {
+                    job: ctx.accounts.job.key(),
+                    mint: job_nft_mint.key(),
+                    owner: ctx.accounts.owner.key(),
+                });
+            }
+            (None, None) => {}
+            _ => return err!(ErrorCodes::JobNftRequired),
+        }
 
         Ok(())
     }
 
     // Settle a job
-    pub fn job_settle(ctx: Context<JobSettle>, job_index: u128) -> Result<()> {
+    pub fn job_settle<'info>(ctx: Context<'_, '_, '_, 'info, JobSettle<'info>>, job_index: u128) -> Result<()> {
+        require!(ctx.accounts.job.status != JobStatus::Closed, ErrorCodes::JobClosed);
         require_keys_eq!(ctx.accounts.token_mint.key(), ctx.accounts.market.token_mint, ErrorCodes::InvalidMint);
 
         let token_mint_key = ctx.accounts.token_mint.key();
@@ -218,6 +890,12 @@ pub mod market_v {
         let current_time = Clock::get()?.unix_timestamp as u64;
 
         let job_rate = ctx.accounts.job.rate;
+        let payouts = utils_mod::provider_payouts(
+            &ctx.accounts.provider_account,
+            &ctx.accounts.provider_token_account,
+            &ctx.accounts.token_mint,
+            ctx.remaining_accounts,
+        )?;
         utils_mod::job_settle_internal(
             &mut ctx.accounts.job,
             job_rate,
@@ -225,28 +903,49 @@ pub mod market_v {
             &ctx.accounts.token_program,
             &ctx.accounts.token_mint,
             &mut ctx.accounts.program_token_account,
-            &mut ctx.accounts.provider_token_account,
+            &payouts,
             &ctx.accounts.credit_mint,
             &mut ctx.accounts.program_credit_token_account,
             signer_seeds,
             &ctx.accounts.owner,
             &mut ctx.accounts.state,
             &mut ctx.accounts.credit_program_usdc_token_account,
+            &mut ctx.accounts.credit_program_reserve,
+            &ctx.accounts.credit_program_provider_tier,
             &ctx.accounts.credit_program,
         )?;
 
+        utils_mod::check_low_balance(
+            &mut ctx.accounts.job,
+            Clock::get()?.unix_timestamp as u64,
+            ctx.accounts.market.notice_period,
+        );
+
         Ok(())
     }
 
-    // Close a job
-    pub fn job_close(ctx: Context<JobClose>, job_index: u128) -> Result<()> {
-        let job = &mut ctx.accounts.job;
-
+    // Close a job immediately, paying the notice period in advance. `job_close_request` bills
+    // the notice as it elapses instead.
+    pub fn job_close<'info>(ctx: Context<'_, '_, '_, 'info, JobClose<'info>>, job_index: u128) -> Result<()> {
         // Ensure the caller is the owner of the job
-        require!(
-            job.owner == *ctx.accounts.owner.key,
-            ErrorCodes::Unauthorized
-        );
+        utils_mod::authorize_job_owner(
+            &mut ctx.accounts.job,
+            ctx.accounts.owner.key,
+            ctx.accounts.job_nft_account.as_deref(),
+        )?;
+
+        require!(ctx.accounts.job.status != JobStatus::Closed, ErrorCodes::JobClosed);
+
+        utils_mod::retire_job_nft(
+            &ctx.accounts.job,
+            ctx.bumps.job,
+            ctx.accounts.job_nft_mint.as_deref(),
+            ctx.accounts.job_nft_account.as_deref(),
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.token_program,
+        )?;
+
+        let job = &mut ctx.accounts.job;
 
         require_keys_eq!(ctx.accounts.token_mint.key(), ctx.accounts.market.token_mint, ErrorCodes::InvalidMint);
 
@@ -257,6 +956,12 @@ pub mod market_v {
         let seeds: &[&[u8]] = &[b"job_token", token_mint_key.as_ref(), &[ctx.bumps.program_token_account]];
         let token_signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
 
+        let payouts = utils_mod::provider_payouts(
+            &ctx.accounts.provider_account,
+            &ctx.accounts.provider_token_account,
+            &ctx.accounts.token_mint,
+            ctx.remaining_accounts,
+        )?;
         utils_mod::job_settle_internal(
             job,
             job.rate,
@@ -264,24 +969,27 @@ pub mod market_v {
             &ctx.accounts.token_program,
             &ctx.accounts.token_mint,
             &mut ctx.accounts.program_token_account,
-            &mut ctx.accounts.provider_token_account,
+            &payouts,
             &ctx.accounts.credit_mint,
             &mut ctx.accounts.program_credit_token_account,
             token_signer_seeds,
             &ctx.accounts.owner,
             &mut ctx.accounts.state,
             &mut ctx.accounts.credit_program_usdc_token_account,
+            &mut ctx.accounts.credit_program_reserve,
+            &ctx.accounts.credit_program_provider_tier,
             &ctx.accounts.credit_program,
         )?;
 
-        // Close the job account and refund the rent to the owner
-        // let job_account = job.to_account_info();
-        // let owner = ctx.accounts.owner.to_account_info();
-
-        // let rent = Rent::get()?;
-        // let lamports = job_account.lamports();
-        // **job_account.lamports.borrow_mut() = 0;
-        // **owner.lamports.borrow_mut() += lamports;
+        // Sponsors follow the beneficiary accounts
+        utils_mod::refund_sponsors(
+            job,
+            &ctx.remaining_accounts[ctx.accounts.provider_account.beneficiaries.len()..],
+            &ctx.accounts.token_mint,
+            &ctx.accounts.program_token_account,
+            &ctx.accounts.token_program,
+            token_signer_seeds,
+        )?;
 
         let balance = job.balance;
         if balance > 0 {
@@ -298,55 +1006,352 @@ pub mod market_v {
                 &mut ctx.accounts.user_credit_token_account,
                 &ctx.accounts.token_program,
                 &ctx.accounts.user_token_account,
+                ctx.accounts.market.credit_refund_policy,
+                ctx.accounts.issuer_credit_token_account.as_deref(),
                 balance,
                 token_signer_seeds,
-                credit_signer_seeds
+                credit_signer_seeds,
+                &ctx.accounts.state,
+                &ctx.accounts.credit_program,
             )?;
         }
 
+        let provider_account = &mut ctx.accounts.provider_account;
+        provider_account.active_jobs = provider_account.active_jobs.checked_sub(1).ok_or(ErrorCodes::ActiveJobsUnderflow)?;
+
+        // Close the job account and its top-up policy, refunding the rent to the owner
+        let owner = ctx.accounts.owner.to_account_info();
+        utils_mod::close_if_exists(&ctx.accounts.topup_policy, &owner)?;
+        utils_mod::close_job(job, owner, ctx.accounts.market.tombstone_jobs)?;
+
         emit!(JobClosed { job: job.key() });
 
         Ok(())
     }
 
+    // Accept a pending job. Billing starts now, the balance must cover at least the notice period.
+    pub fn job_accept(
+        ctx: Context<JobAccept>,
+        _job_index: u128, // Job index to identify the job
+    ) -> Result<()> {
+        utils_mod::require_provider_permission(
+            &ctx.accounts.provider_account,
+            ctx.accounts.operator.as_deref(),
+            ctx.accounts.authority.key,
+            OPERATOR_ACCEPT_JOBS,
+        )?;
+        // Same as job_open, providers winding down or suspended take no new jobs
+        require!(ctx.accounts.provider_account.status == ProviderStatus::Active, ErrorCodes::ProviderNotActive);
+        require!(!ctx.accounts.provider_account.suspended, ErrorCodes::ProviderSuspended);
+
+        let job = &mut ctx.accounts.job;
+        require!(job.status == JobStatus::Pending, ErrorCodes::JobNotPending);
+
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        require!(
+            job.accept_deadline == 0 || current_time <= job.accept_deadline,
+            ErrorCodes::AcceptDeadlinePassed
+        );
+
+        utils_mod::set_job_status(job, JobStatus::Active);
+        job.accept_deadline = 0;
+        job.last_settled = current_time;
+
+        // The balance must cover the notice period, which is billed as it elapses
+        require!(
+            job.balance >= utils_mod::notice_reserve(job, current_time, ctx.accounts.market.notice_period),
+            ErrorCodes::InsufficientBalance
+        );
+
+        emit!(JobAccepted {
+            job: job.key(),
+            provider: job.provider,
+            timestamp: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    // Reject a pending job, refunding its full balance to the owner
+    pub fn job_reject<'info>(
+        ctx: Context<'_, '_, '_, 'info, JobCancelPending<'info>>,
+        _job_index: u128, // Job index to identify the job
+    ) -> Result<()> {
+        utils_mod::require_provider_permission(
+            &ctx.accounts.provider_account,
+            ctx.accounts.operator.as_deref(),
+            ctx.accounts.authority.key,
+            OPERATOR_ACCEPT_JOBS,
+        )?;
+
+        let refunded = utils_mod::cancel_pending_job(ctx.accounts, &ctx.bumps, ctx.remaining_accounts)?;
+
+        emit!(JobRejected {
+            job: ctx.accounts.job.key(),
+            provider: ctx.accounts.job.provider,
+            refunded,
+        });
+
+        Ok(())
+    }
+
+    // Expire a pending job the provider did not accept in time, refunding its full balance to
+    // the owner. Callable by anyone.
+    pub fn job_expire<'info>(
+        ctx: Context<'_, '_, '_, 'info, JobCancelPending<'info>>,
+        _job_index: u128, // Job index to identify the job
+    ) -> Result<()> {
+        let job = &ctx.accounts.job;
+        require!(
+            job.accept_deadline != 0 && (Clock::get()?.unix_timestamp as u64) > job.accept_deadline,
+            ErrorCodes::AcceptDeadlineNotPassed
+        );
+
+        let refunded = utils_mod::cancel_pending_job(ctx.accounts, &ctx.bumps, ctx.remaining_accounts)?;
+
+        emit!(JobExpired {
+            job: ctx.accounts.job.key(),
+            provider: ctx.accounts.job.provider,
+            refunded,
+        });
+
+        Ok(())
+    }
+
+    // Provider ends a job. Billing continues for the market notice period and then stops.
+    pub fn job_terminate_by_provider(
+        ctx: Context<JobTerminateByProvider>,
+        _job_index: u128, // Job index to identify the job
+    ) -> Result<()> {
+        let job = &mut ctx.accounts.job;
+
+        // Ensure the caller controls the job's provider
+        require!(
+            ctx.accounts.provider_account.owner == *ctx.accounts.authority.key,
+            ErrorCodes::Unauthorized
+        );
+
+        require!(job.status == JobStatus::Active, ErrorCodes::InvalidJobStatus);
+
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        job.end_at = current_time + ctx.accounts.market.notice_period;
+        utils_mod::set_job_status(job, JobStatus::Terminated);
+
+        emit!(JobTerminationStarted {
+            job: job.key(),
+            provider: job.provider,
+            terminate_at: job.end_at,
+        });
+
+        Ok(())
+    }
+
+    // Finish a provider termination once its notice has elapsed, refunding the rest to the owner
+    pub fn job_terminate_finalize<'info>(
+        ctx: Context<'_, '_, '_, 'info, JobFinalize<'info>>,
+        _job_index: u128, // Job index to identify the job
+    ) -> Result<()> {
+        require!(ctx.accounts.job.status == JobStatus::Terminated, ErrorCodes::JobNotTerminating);
+
+        let refunded = utils_mod::finalize_job(ctx.accounts, &ctx.bumps, ctx.remaining_accounts)?;
+
+        emit!(JobTerminatedByProvider {
+            job: ctx.accounts.job.key(),
+            provider: ctx.accounts.job.provider,
+            refunded,
+        });
+
+        Ok(())
+    }
+
+    // Preview settling a job now: the amount due and its credit/token split, what the owner could
+    // withdraw and the runway left. Meant to be simulated by clients.
+    pub fn job_settlement_preview(
+        ctx: Context<JobView>,
+        _job_index: u128, // Job index to identify the job
+    ) -> Result<JobSettlementPreview> {
+        let job = &ctx.accounts.job;
+        let now = Clock::get()?.unix_timestamp as u64;
+
+        let amount_due = utils_mod::amount_due(job, now);
+        let (credit_amount, token_amount) = utils_mod::calculate_token_split(amount_due, job.credit_balance);
+        let reserve = utils_mod::notice_reserve(job, now, ctx.accounts.market.notice_period);
+        let mut refundable_balance = (job.balance - amount_due).saturating_sub(reserve);
+        // Same limit as job_withdraw, while sponsored the owner only withdraws its own tokens
+        if job.sponsors_outstanding > 0 {
+            let owner_tokens = (job.balance - amount_due - (job.credit_balance - credit_amount))
+                .saturating_sub(job.sponsored_balance);
+            refundable_balance = refundable_balance.min(owner_tokens);
+        }
+
+        Ok(JobSettlementPreview {
+            amount_due,
+            credit_amount,
+            token_amount,
+            refundable_balance,
+            runway: utils_mod::job_runway(job, now),
+        })
+    }
+
+    // Seconds of billing a job's balance still pays for. Meant to be simulated by clients.
+    pub fn job_runway(
+        ctx: Context<JobView>,
+        _job_index: u128, // Job index to identify the job
+    ) -> Result<u64> {
+        let now = Clock::get()?.unix_timestamp as u64;
+        Ok(utils_mod::job_runway(&ctx.accounts.job, now))
+    }
+
+    // Close a job whose balance can no longer cover the notice period. Callable by anyone, the
+    // caller receives the market's reap bounty out of the job rent.
+    pub fn job_reap<'info>(
+        ctx: Context<'_, '_, '_, 'info, JobFinalize<'info>>,
+        _job_index: u128, // Job index to identify the job
+    ) -> Result<()> {
+        require!(
+            matches!(ctx.accounts.job.status, JobStatus::Active | JobStatus::Closing | JobStatus::Terminated),
+            ErrorCodes::InvalidJobStatus
+        );
+
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        let reap_bounty = ctx.accounts.market.reap_bounty;
+        let (refunded, bounty) = utils_mod::wind_up_job(
+            ctx.accounts,
+            &ctx.bumps,
+            ctx.remaining_accounts,
+            current_time,
+            Some(reap_bounty),
+        )?;
+
+        emit!(JobReaped {
+            job: ctx.accounts.job.key(),
+            provider: ctx.accounts.job.provider,
+            keeper: ctx.accounts.authority.key(),
+            refunded,
+            bounty,
+        });
+
+        Ok(())
+    }
+
+    // Start closing a job. The rate is frozen and billing continues until the notice period ends.
+    pub fn job_close_request<'info>(
+        ctx: Context<'_, '_, '_, 'info, JobCloseRequest<'info>>,
+        _job_index: u128, // Job index to identify the job
+    ) -> Result<()> {
+        utils_mod::authorize_job_owner(
+            &mut ctx.accounts.job,
+            ctx.accounts.owner.key,
+            ctx.accounts.job_nft_account.as_deref(),
+        )?;
+        require!(ctx.accounts.job.status == JobStatus::Active, ErrorCodes::InvalidJobStatus);
+        require_keys_eq!(ctx.accounts.token_mint.key(), ctx.accounts.market.token_mint, ErrorCodes::InvalidMint);
+
+        let token_mint_key = ctx.accounts.token_mint.key();
+        let seeds: &[&[u8]] = &[b"job_token", token_mint_key.as_ref(), &[ctx.bumps.program_token_account]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
+
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Bill usage so far, the notice is billed as it elapses
+        let job_rate = ctx.accounts.job.rate;
+        let payouts = utils_mod::provider_payouts(
+            &ctx.accounts.provider_account,
+            &ctx.accounts.provider_token_account,
+            &ctx.accounts.token_mint,
+            ctx.remaining_accounts,
+        )?;
+        utils_mod::job_settle_internal(
+            &mut ctx.accounts.job,
+            job_rate,
+            current_time,
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint,
+            &mut ctx.accounts.program_token_account,
+            &payouts,
+            &ctx.accounts.credit_mint,
+            &mut ctx.accounts.program_credit_token_account,
+            signer_seeds,
+            &ctx.accounts.owner,
+            &mut ctx.accounts.state,
+            &mut ctx.accounts.credit_program_usdc_token_account,
+            &mut ctx.accounts.credit_program_reserve,
+            &ctx.accounts.credit_program_provider_tier,
+            &ctx.accounts.credit_program,
+        )?;
+
+        let job = &mut ctx.accounts.job;
+        job.end_at = current_time + ctx.accounts.market.notice_period;
+        utils_mod::set_job_status(job, JobStatus::Closing);
+
+        emit!(JobCloseRequested {
+            job: job.key(),
+            close_at: job.end_at,
+        });
+
+        Ok(())
+    }
+
+    // Finish a requested close once its notice has elapsed, billing actual usage and refunding
+    // the rest to the owner
+    pub fn job_close_finalize<'info>(
+        ctx: Context<'_, '_, '_, 'info, JobFinalize<'info>>,
+        _job_index: u128, // Job index to identify the job
+    ) -> Result<()> {
+        require!(ctx.accounts.job.status == JobStatus::Closing, ErrorCodes::JobNotClosing);
+
+        utils_mod::finalize_job(ctx.accounts, &ctx.bumps, ctx.remaining_accounts)?;
+
+        emit!(JobClosed { job: ctx.accounts.job.key() });
+
+        Ok(())
+    }
+
     // Deposit tokens into a job
-    pub fn job_deposit(
-        ctx: Context<JobDeposit>,
+    pub fn job_deposit<'info>(
+        ctx: Context<'_, '_, '_, 'info, JobDeposit<'info>>,
         job_index: u128, // Job index to identify the job
         amount: u64,    // Amount of tokens to deposit
     ) -> Result<()> {
         let job = &mut ctx.accounts.job;
 
-        // Ensure the job exists
-        require!(
-            job.owner != Pubkey::default(),
-            ErrorCodes::JobNotFound
-        );
+        // Ensure the job is still open
+        require!(job.status != JobStatus::Closed, ErrorCodes::JobClosed);
 
         require_keys_eq!(ctx.accounts.token_mint.key(), ctx.accounts.market.token_mint, ErrorCodes::InvalidMint);
         require!(amount > 0, ErrorCodes::InvalidAmount);
+        // Winding-down jobs only bill their notice, deposits would just be refunded
+        require!(job.status != JobStatus::Terminated, ErrorCodes::JobTerminating);
+        require!(job.status != JobStatus::Closing, ErrorCodes::JobClosing);
 
         let current_time = Clock::get()?.unix_timestamp as u64;
-        let notice_period = ctx.accounts.market.notice_period;
 
         let token_mint_key = ctx.accounts.token_mint.key();
         let seeds: &[&[u8]] = &[b"job_token", token_mint_key.as_ref(), &[ctx.bumps.program_token_account]];
         let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
 
+        let payouts = utils_mod::provider_payouts(
+            &ctx.accounts.provider_account,
+            &ctx.accounts.provider_token_account,
+            &ctx.accounts.token_mint,
+            ctx.remaining_accounts,
+        )?;
         let res = utils_mod::job_settle_internal(
             job,
             job.rate,
-            current_time + notice_period,
+            current_time,
             &ctx.accounts.token_program,
             &ctx.accounts.token_mint,
             &mut ctx.accounts.program_token_account,
-            &mut ctx.accounts.provider_token_account,
+            &payouts,
             &ctx.accounts.credit_mint,
             &mut ctx.accounts.program_credit_token_account,
             signer_seeds,
             &ctx.accounts.owner,
             &mut ctx.accounts.state,
             &mut ctx.accounts.credit_program_usdc_token_account,
+            &mut ctx.accounts.credit_program_reserve,
+            &ctx.accounts.credit_program_provider_tier,
             &ctx.accounts.credit_program,
         )?;
         require!(res, ErrorCodes::InsufficientFundsToReviseRate);
@@ -359,33 +1364,38 @@ pub mod market_v {
             &mut ctx.accounts.token_mint,
             &mut ctx.accounts.user_token_account,
             &mut ctx.accounts.program_token_account,
-            &ctx.accounts.owner,
+            &ctx.accounts.owner.to_account_info(),
+            &[],
             &ctx.accounts.token_program,
             amount
         )?;
 
+        utils_mod::check_low_balance(
+            &mut ctx.accounts.job,
+            Clock::get()?.unix_timestamp as u64,
+            ctx.accounts.market.notice_period,
+        );
+
         Ok(())
     }
 
     // Withdraw tokens from a job
-    pub fn job_withdraw(
-        ctx: Context<JobWithdraw>,
+    pub fn job_withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, JobWithdraw<'info>>,
         job_index: u128, // Job index to identify the job
         amount: u64,    // Amount of tokens to withdraw
     ) -> Result<()> {
-        let job = &mut ctx.accounts.job;
+        // Ensure the caller is the job owner
+        utils_mod::authorize_job_owner(
+            &mut ctx.accounts.job,
+            ctx.accounts.owner.key,
+            ctx.accounts.job_nft_account.as_deref(),
+        )?;
 
-        // Ensure the job exists
-        require!(
-            job.owner != Pubkey::default(),
-            ErrorCodes::JobNotFound
-        );
+        let job = &mut ctx.accounts.job;
 
-        // Ensure the caller is the job owner
-        require!(
-            job.owner == *ctx.accounts.owner.key,
-            ErrorCodes::Unauthorized
-        );
+        // Ensure the job is still open
+        require!(job.status != JobStatus::Closed, ErrorCodes::JobClosed);
 
         require_keys_eq!(ctx.accounts.token_mint.key(), ctx.accounts.market.token_mint, ErrorCodes::InvalidMint);
 
@@ -398,24 +1408,41 @@ pub mod market_v {
         let seeds: &[&[u8]] = &[b"job_token", token_mint_key.as_ref(), &[ctx.bumps.program_token_account]];
         let token_signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
 
+        let payouts = utils_mod::provider_payouts(
+            &ctx.accounts.provider_account,
+            &ctx.accounts.provider_token_account,
+            &ctx.accounts.token_mint,
+            ctx.remaining_accounts,
+        )?;
         let res = utils_mod::job_settle_internal(
             job,
             job.rate,
-            current_time + notice_period,
+            current_time,
             &ctx.accounts.token_program,
             &ctx.accounts.token_mint,
             &mut ctx.accounts.program_token_account,
-            &mut ctx.accounts.provider_token_account,
+            &payouts,
             &ctx.accounts.credit_mint,
             &mut ctx.accounts.program_credit_token_account,
             token_signer_seeds,
             &ctx.accounts.owner,
             &mut ctx.accounts.state,
             &mut ctx.accounts.credit_program_usdc_token_account,
+            &mut ctx.accounts.credit_program_reserve,
+            &ctx.accounts.credit_program_provider_tier,
             &ctx.accounts.credit_program,
         )?;
         require!(res, ErrorCodes::InsufficientFundsToReviseRate);
 
+        // The remaining balance must still pay for the notice period
+        let reserve = utils_mod::notice_reserve(job, current_time, notice_period);
+        require!(job.balance >= reserve.saturating_add(amount), ErrorCodes::InsufficientBalance);
+        // While sponsored, the owner only withdraws its own tokens
+        if job.sponsors_outstanding > 0 {
+            let owner_tokens = (job.balance - job.credit_balance).saturating_sub(job.sponsored_balance);
+            require!(amount <= owner_tokens, ErrorCodes::SponsoredFunds);
+        }
+
         let credit_mint_key = ctx.accounts.credit_mint.key();
         let seeds: &[&[u8]] = &[b"credit_token", credit_mint_key.as_ref(), &[ctx.bumps.program_credit_token_account]];
         let credit_signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
@@ -429,28 +1456,59 @@ pub mod market_v {
             &mut ctx.accounts.user_credit_token_account,
             &ctx.accounts.token_program,
             &ctx.accounts.user_token_account,
+            ctx.accounts.market.credit_refund_policy,
+            ctx.accounts.issuer_credit_token_account.as_deref(),
             amount,
             token_signer_seeds,
-            credit_signer_seeds
+            credit_signer_seeds,
+            &ctx.accounts.state,
+            &ctx.accounts.credit_program,
         )?;
 
+        utils_mod::check_low_balance(
+            &mut ctx.accounts.job,
+            Clock::get()?.unix_timestamp as u64,
+            ctx.accounts.market.notice_period,
+        );
+
         Ok(())
     }
 
-    pub fn job_revise_rate(
-        ctx: Context<JobReviseRate>,
+    pub fn job_revise_rate<'info>(
+        ctx: Context<'_, '_, '_, 'info, JobReviseRate<'info>>,
         job_index: u128, // Job index to identify the job
         new_rate: u64,  // New rate to propose
     ) -> Result<()> {
+        // Ensure the caller is the job owner
+        utils_mod::authorize_job_owner(
+            &mut ctx.accounts.job,
+            ctx.accounts.owner.key,
+            ctx.accounts.job_nft_account.as_deref(),
+        )?;
+
+        utils_mod::require_rate_card(
+            &ctx.accounts.provider_account,
+            ctx.accounts.rate_card.as_deref(),
+            &ctx.accounts.job.metadata,
+            new_rate,
+            Clock::get()?.unix_timestamp,
+        )?;
+
         let token_mint_key = ctx.accounts.token_mint.key();
         let seeds: &[&[u8]] = &[b"job_token", token_mint_key.as_ref(), &[ctx.bumps.program_token_account]];
         let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
 
+        let payouts = utils_mod::provider_payouts(
+            &ctx.accounts.provider_account,
+            &ctx.accounts.provider_token_account,
+            &ctx.accounts.token_mint,
+            ctx.remaining_accounts,
+        )?;
         utils_mod::job_revise_rate_internal(
             &mut ctx.accounts.job,
             &ctx.accounts.token_mint,
             &mut ctx.accounts.program_token_account,
-            &mut ctx.accounts.provider_token_account,
+            &payouts,
             &mut ctx.accounts.credit_mint,
             &mut ctx.accounts.program_credit_token_account,
             &ctx.accounts.token_program,
@@ -460,46 +1518,404 @@ pub mod market_v {
             &ctx.accounts.owner,
             &mut ctx.accounts.state,
             &mut ctx.accounts.credit_program_usdc_token_account,
+            &mut ctx.accounts.credit_program_reserve,
+            &ctx.accounts.credit_program_provider_tier,
             &ctx.accounts.credit_program,
         )?;
 
+        utils_mod::check_low_balance(
+            &mut ctx.accounts.job,
+            Clock::get()?.unix_timestamp as u64,
+            ctx.accounts.market.notice_period,
+        );
+
         Ok(())
     }
 
-    pub fn job_metadata_update(
-        ctx: Context<JobMetadataUpdate>,
-        job_index: u128, // Job index to identify the job
-        new_metadata: String, // New metadata to set
+    // Propose a new job owner. The job keeps its index, balance and credits, so refunds and
+    // credit refunds go to the new owner once it accepts. Proposing `Pubkey::default()`
+    // cancels a pending transfer.
+    pub fn job_transfer_ownership(
+        ctx: Context<JobOwnerAction>,
+        _job_index: u128, // Job index to identify the job
+        new_owner: Pubkey,
     ) -> Result<()> {
         let job = &mut ctx.accounts.job;
+        require!(job.owner == *ctx.accounts.owner.key, ErrorCodes::Unauthorized);
+        require!(job.status != JobStatus::Closed, ErrorCodes::JobClosed);
+        require!(new_owner != job.owner, ErrorCodes::InvalidJobOwner);
+        // Tokenized jobs move with their NFT
+        require!(job.nft_mint == Pubkey::default(), ErrorCodes::JobTokenized);
+        job.pending_owner = new_owner;
+
+        emit!(JobOwnershipTransferStarted {
+            job: job.key(),
+            new_owner,
+        });
 
-        // Ensure the job exists
-        require!(
-            job.owner != Pubkey::default(),
-            ErrorCodes::JobNotFound
-        );
-
-        // Ensure the caller is the job owner
-        require!(
-            job.owner == *ctx.accounts.owner.key,
-            ErrorCodes::Unauthorized
-        );
-
-        // check if the new_metadata is not same as the old one
-        require!(job.metadata != new_metadata, ErrorCodes::UnchangedMetadata);
+        Ok(())
+    }
 
-        // Update the metadata
-        job.metadata = new_metadata;
+    // Accept a proposed job ownership. Top-up policies of the previous owner stop applying.
+    pub fn job_accept_ownership(
+        ctx: Context<JobAcceptOwnership>,
+        _job_index: u128, // Job index to identify the job
+    ) -> Result<()> {
+        let job = &mut ctx.accounts.job;
+        require!(job.status != JobStatus::Closed, ErrorCodes::JobClosed);
+        let old_owner = job.owner;
+        job.owner = job.pending_owner;
+        job.pending_owner = Pubkey::default();
 
-        emit!(JobMetadataUpdated {
+        emit!(JobOwnershipTransferred {
             job: job.key(),
-            new_metadata: job.metadata.clone()
+            old_owner,
+            new_owner: job.owner,
+            balance: job.balance,
+            credit_balance: job.credit_balance,
         });
 
         Ok(())
     }
-    mod utils_mod {
-        use super::*;
+
+    // Set the runway, in seconds, below which `JobLowBalance` is emitted. 0 only warns once the
+    // runway drops below the notice period.
+    pub fn job_set_runway_threshold(
+        ctx: Context<JobOwnerAction>,
+        _job_index: u128, // Job index to identify the job
+        runway_threshold: u64,
+    ) -> Result<()> {
+        utils_mod::authorize_job_owner(
+            &mut ctx.accounts.job,
+            ctx.accounts.owner.key,
+            ctx.accounts.job_nft_account.as_deref(),
+        )?;
+
+        let job = &mut ctx.accounts.job;
+        require!(job.status != JobStatus::Closed, ErrorCodes::JobClosed);
+
+        job.runway_threshold = runway_threshold;
+        // Re-evaluate against the new threshold
+        job.low_balance_level = LowBalanceLevel::None;
+        utils_mod::check_low_balance(
+            job,
+            Clock::get()?.unix_timestamp as u64,
+            ctx.accounts.market.notice_period,
+        );
+
+        emit!(JobRunwayThresholdUpdated {
+            job: job.key(),
+            runway_threshold,
+        });
+
+        Ok(())
+    }
+
+    // Set up automatic top-ups for a job. Keepers top the job up to `target_runway` seconds out of
+    // the owner's token accounts, which must approve the market's top-up delegate.
+    pub fn job_topup_policy_set(
+        ctx: Context<JobTopupPolicySet>,
+        _job_index: u128, // Job index to identify the job
+        target_runway: u64,
+        max_per_topup: u64,
+        monthly_cap: u64,
+    ) -> Result<()> {
+        utils_mod::authorize_job_owner(
+            &mut ctx.accounts.job,
+            ctx.accounts.owner.key,
+            ctx.accounts.job_nft_account.as_deref(),
+        )?;
+
+        let job = &ctx.accounts.job;
+        require!(job.status != JobStatus::Closed, ErrorCodes::JobClosed);
+        require!(
+            target_runway > 0 && max_per_topup > 0 && monthly_cap >= max_per_topup,
+            ErrorCodes::InvalidTopupPolicy
+        );
+
+        let policy = &mut ctx.accounts.policy;
+        if policy.job == Pubkey::default() {
+            policy.job = job.key();
+            policy.period_start = Clock::get()?.unix_timestamp as u64;
+        }
+        policy.owner = job.owner;
+        policy.target_runway = target_runway;
+        policy.max_per_topup = max_per_topup;
+        policy.monthly_cap = monthly_cap;
+
+        emit!(JobTopupPolicyUpdated {
+            job: policy.job,
+            target_runway,
+            max_per_topup,
+            monthly_cap,
+        });
+
+        Ok(())
+    }
+
+    // Remove a job's top-up policy. The owner that set it up or the current job owner can remove
+    // it.
+    pub fn job_topup_policy_remove(
+        ctx: Context<JobTopupPolicyRemove>,
+        _job_index: u128, // Job index to identify the job
+    ) -> Result<()> {
+        let signer = ctx.accounts.owner.key();
+        if ctx.accounts.policy.owner != signer {
+            utils_mod::authorize_job_owner(&mut ctx.accounts.job, &signer, ctx.accounts.job_nft_account.as_deref())?;
+        }
+
+        emit!(JobTopupPolicyRemoved {
+            job: ctx.accounts.job.key(),
+        });
+
+        Ok(())
+    }
+
+    // Top a job up to its policy's target runway from the owner's delegated token accounts.
+    // Callable by anyone.
+    pub fn job_auto_topup(
+        ctx: Context<JobAutoTopup>,
+        _job_index: u128, // Job index to identify the job
+    ) -> Result<()> {
+        let owner = utils_mod::job_owner(&ctx.accounts.job, ctx.accounts.job_nft_account.as_deref())?;
+        ctx.accounts.job.owner = owner;
+        require!(
+            ctx.accounts.user_token_account.owner == owner && ctx.accounts.user_credit_token_account.owner == owner,
+            ErrorCodes::Unauthorized
+        );
+
+        let job = &ctx.accounts.job;
+        require!(job.status == JobStatus::Active, ErrorCodes::InvalidJobStatus);
+        // Policies are tied to the owner that set them up
+        require!(ctx.accounts.policy.owner == job.owner, ErrorCodes::InvalidTopupPolicy);
+
+        let now = Clock::get()?.unix_timestamp as u64;
+        let runway = utils_mod::job_runway(job, now);
+        let policy = &mut ctx.accounts.policy;
+        require!(runway < policy.target_runway, ErrorCodes::TopupNotNeeded);
+
+        if now >= policy.period_start + TOPUP_PERIOD {
+            policy.period_start = now;
+            policy.period_topped_up = 0;
+        }
+
+        let needed = utils_mod::calculate_amount_used(job.rate, policy.target_runway - runway);
+        let amount = needed
+            .min(policy.max_per_topup)
+            .min(policy.monthly_cap.saturating_sub(policy.period_topped_up));
+        require!(amount > 0, ErrorCodes::TopupCapReached);
+        policy.period_topped_up += amount;
+
+        let seeds: &[&[u8]] = &[b"topup_delegate", &[ctx.bumps.topup_delegate]];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        utils_mod::deposit_token(
+            &mut ctx.accounts.job,
+            &mut ctx.accounts.credit_mint,
+            &mut ctx.accounts.user_credit_token_account,
+            &mut ctx.accounts.program_credit_token_account,
+            &mut ctx.accounts.token_mint,
+            &mut ctx.accounts.user_token_account,
+            &mut ctx.accounts.program_token_account,
+            &ctx.accounts.topup_delegate.to_account_info(),
+            signer_seeds,
+            &ctx.accounts.token_program,
+            amount
+        )?;
+
+        emit!(JobAutoToppedUp {
+            job: ctx.accounts.job.key(),
+            keeper: ctx.accounts.keeper.key(),
+            amount,
+            runway: utils_mod::job_runway(&ctx.accounts.job, now),
+        });
+
+        utils_mod::check_low_balance(&mut ctx.accounts.job, now, ctx.accounts.market.notice_period);
+
+        Ok(())
+    }
+
+    // Choose how unused funds are shared between sponsors. Only while no sponsorship is
+    // outstanding.
+    pub fn job_set_sponsor_refund_policy(
+        ctx: Context<JobOwnerAction>,
+        _job_index: u128, // Job index to identify the job
+        policy: SponsorRefundPolicy,
+    ) -> Result<()> {
+        utils_mod::authorize_job_owner(
+            &mut ctx.accounts.job,
+            ctx.accounts.owner.key,
+            ctx.accounts.job_nft_account.as_deref(),
+        )?;
+
+        let job = &mut ctx.accounts.job;
+        require!(job.status != JobStatus::Closed, ErrorCodes::JobClosed);
+        require!(job.sponsors_outstanding == 0, ErrorCodes::SponsorsOutstanding);
+
+        job.sponsor_refund_policy = policy;
+
+        emit!(JobSponsorRefundPolicyUpdated {
+            job: job.key(),
+            policy,
+        });
+
+        Ok(())
+    }
+
+    // Fund someone else's job. The sponsor keeps a claim on unused funds, refunded through
+    // `job_sponsor_refund` or when the job is closed.
+    pub fn job_sponsor_deposit(
+        ctx: Context<JobSponsorDeposit>,
+        _job_index: u128, // Job index to identify the job
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0 && amount >= ctx.accounts.market.min_sponsor_deposit, ErrorCodes::InvalidAmount);
+        require!(
+            matches!(ctx.accounts.job.status, JobStatus::Pending | JobStatus::Active),
+            ErrorCodes::InvalidJobStatus
+        );
+        require!(ctx.accounts.job.sponsors_outstanding < MAX_JOB_SPONSORS, ErrorCodes::TooManySponsors);
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.sponsor_token_account.to_account_info(),
+            to: ctx.accounts.program_token_account.to_account_info(),
+            authority: ctx.accounts.sponsor.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        let job = &mut ctx.accounts.job;
+        let sponsorship = &mut ctx.accounts.sponsorship;
+        sponsorship.job = job.key();
+        sponsorship.sponsor = ctx.accounts.sponsor.key();
+        sponsorship.seq = job.sponsor_seq;
+        sponsorship.amount = amount;
+
+        job.sponsor_seq += 1;
+        job.sponsors_outstanding += 1;
+        job.sponsor_claims += amount;
+        job.sponsored_balance += amount;
+        job.balance += amount;
+
+        emit!(JobSponsored {
+            job: job.key(),
+            sponsor: sponsorship.sponsor,
+            seq: sponsorship.seq,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Refund a sponsorship its share of the unused funds and close it. The sponsor or owner can
+    // refund at any time, anyone once the job is winding down or can no longer cover its notice.
+    pub fn job_sponsor_refund(
+        ctx: Context<JobSponsorRefund>,
+        _job_index: u128, // Job index to identify the job
+    ) -> Result<()> {
+        let authority = *ctx.accounts.authority.key;
+        // Tokenized jobs are owned by whoever holds their NFT
+        let is_owner = utils_mod::job_owner(&ctx.accounts.job, ctx.accounts.job_nft_account.as_deref())
+            .is_ok_and(|owner| owner == authority);
+
+        let job = &ctx.accounts.job;
+        let sponsorship = &ctx.accounts.sponsorship;
+        let now = Clock::get()?.unix_timestamp as u64;
+
+        let due = utils_mod::amount_due(job, now);
+        let reserve = utils_mod::notice_reserve(job, now, ctx.accounts.market.notice_period);
+        let reapable = job.status != JobStatus::Pending && job.balance - due < reserve.max(1);
+
+        require!(
+            authority == sponsorship.sponsor
+                || is_owner
+                || matches!(job.status, JobStatus::Closing | JobStatus::Terminated)
+                || reapable,
+            ErrorCodes::Unauthorized
+        );
+        if job.sponsor_refund_policy == SponsorRefundPolicy::Lifo {
+            require!(sponsorship.seq + 1 == job.sponsor_seq, ErrorCodes::SponsorRefundOrder);
+        }
+
+        // Running jobs keep paying for usage and the notice before sponsors are refunded
+        let owed = if reapable { due } else { due + reserve };
+        let refund = utils_mod::sponsor_refund_amount(job, sponsorship, owed);
+
+        if refund > 0 {
+            let token_mint_key = ctx.accounts.token_mint.key();
+            let seeds: &[&[u8]] = &[b"job_token", token_mint_key.as_ref(), &[ctx.bumps.program_token_account]];
+            let signer_seeds: &[&[&[u8]]] = &[seeds];
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.program_token_account.to_account_info(),
+                to: ctx.accounts.sponsor_token_account.to_account_info(),
+                authority: ctx.accounts.program_token_account.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+                .with_signer(signer_seeds);
+            token::transfer(cpi_ctx, refund)?;
+        }
+
+        let job = &mut ctx.accounts.job;
+        let sponsorship = &ctx.accounts.sponsorship;
+        utils_mod::release_sponsorship(job, sponsorship, refund);
+
+        emit!(JobSponsorRefunded {
+            job: job.key(),
+            sponsor: sponsorship.sponsor,
+            seq: sponsorship.seq,
+            amount: refund,
+        });
+
+        Ok(())
+    }
+
+    pub fn job_metadata_update(
+        ctx: Context<JobMetadataUpdate>,
+        job_index: u128, // Job index to identify the job
+        new_metadata: String, // New metadata to set
+    ) -> Result<()> {
+        // Ensure the caller is the job owner
+        utils_mod::authorize_job_owner(
+            &mut ctx.accounts.job,
+            ctx.accounts.owner.key,
+            ctx.accounts.job_nft_account.as_deref(),
+        )?;
+
+        let job = &mut ctx.accounts.job;
+
+        // Ensure the job is still open
+        require!(job.status != JobStatus::Closed, ErrorCodes::JobClosed);
+
+        // check if the new_metadata is not same as the old one
+        require!(job.metadata != new_metadata, ErrorCodes::UnchangedMetadata);
+
+        // The job's rate must still meet the card for the new instance type and region
+        utils_mod::require_rate_card(
+            &ctx.accounts.provider_account,
+            ctx.accounts.rate_card.as_deref(),
+            &new_metadata,
+            job.rate,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        // Update the metadata
+        job.metadata = new_metadata;
+
+        emit!(JobMetadataUpdated {
+            job: job.key(),
+            new_metadata: job.metadata.clone()
+        });
+
+        Ok(())
+    }
+    pub(crate) mod utils_mod {
+        use super::*;
+
+        // Token account receiving a share of a provider's earnings
+        pub struct Payout<'info> {
+            pub token_account: AccountInfo<'info>,
+            pub owner: Pubkey,
+            pub share_bps: u16,
+        }
 
         pub fn update_token_util<'info>(
             market: &mut Account<'info, Market>,
@@ -517,6 +1933,77 @@ pub mod market_v {
             Ok(())
         }
 
+        // Current owner of a job. Tokenized jobs are owned by whoever holds their NFT.
+        pub fn job_owner(job: &Job, job_nft_account: Option<&Account<TokenAccount>>) -> Result<Pubkey> {
+            if job.nft_mint == Pubkey::default() {
+                return Ok(job.owner);
+            }
+
+            let job_nft_account = job_nft_account.ok_or(ErrorCodes::JobNftRequired)?;
+            require!(
+                job_nft_account.mint == job.nft_mint && job_nft_account.amount == 1,
+                ErrorCodes::Unauthorized
+            );
+            Ok(job_nft_account.owner)
+        }
+
+        // Ensure the signer owns the job. The recorded owner of a tokenized job follows its NFT
+        // holder.
+        pub fn authorize_job_owner(
+            job: &mut Job,
+            signer: &Pubkey,
+            job_nft_account: Option<&Account<TokenAccount>>,
+        ) -> Result<()> {
+            job.owner = job_owner(job, job_nft_account)?;
+            require!(job.owner == *signer, ErrorCodes::Unauthorized);
+
+            Ok(())
+        }
+
+        // Point the recorded owner at the job's current owner and check the accounts receiving
+        // the owner's rent and refunds belong to it
+        pub fn resolve_job_owner(
+            job: &mut Job,
+            job_nft_account: Option<&Account<TokenAccount>>,
+            owner: &AccountInfo,
+            user_token_account: &Account<TokenAccount>,
+            user_credit_token_account: &Account<TokenAccount>,
+        ) -> Result<()> {
+            job.owner = job_owner(job, job_nft_account)?;
+            require!(
+                owner.key() == job.owner
+                    && user_token_account.owner == job.owner
+                    && user_credit_token_account.owner == job.owner,
+                ErrorCodes::Unauthorized
+            );
+
+            Ok(())
+        }
+
+        // Ensure the signer is the provider or one of its operators holding `permission`
+        pub fn require_provider_permission<'info>(
+            provider: &Account<'info, Provider>,
+            operator: Option<&Account<'info, ProviderOperator>>,
+            signer: &Pubkey,
+            permission: u8,
+        ) -> Result<()> {
+            if provider.owner == *signer {
+                return Ok(());
+            }
+
+            let operator = operator.ok_or(ErrorCodes::Unauthorized)?;
+            require!(
+                operator.provider == provider.key() && operator.operator == *signer,
+                ErrorCodes::Unauthorized
+            );
+            require!(
+                operator.permissions & permission == permission,
+                ErrorCodes::MissingOperatorPermission
+            );
+
+            Ok(())
+        }
+
         // Reusable function to settle a job
         pub(in crate::market_v) fn settle_job<'info>(
             job: &mut Account<'info, Job>,
@@ -567,7 +2054,7 @@ pub mod market_v {
             job: &mut Account<'info, Job>,
             token_mint: &Account<'info, Mint>,
             program_token_account: &mut Account<'info, TokenAccount>,
-            provider_token_account: &mut Account<'info, TokenAccount>,
+            payouts: &[Payout<'info>],
             credit_mint: &Account<'info, Mint>,
             program_credit_token_account: &mut Account<'info, TokenAccount>,
             token_program: &Program<'info, Token>,
@@ -577,10 +2064,15 @@ pub mod market_v {
             signer: &Signer<'info>,
             state: &mut UncheckedAccount<'info>,
             credit_program_usdc_token_account: &mut UncheckedAccount<'info>,
+            credit_program_reserve: &mut UncheckedAccount<'info>,
+            credit_program_provider_tier: &UncheckedAccount<'info>,
             credit_program: &Program<'info, OysterCredits>,
         ) -> Result<()> {
             require!(new_rate > 0, ErrorCodes::InvalidRate);
             require!(job.rate != new_rate, ErrorCodes::UnchangedRate);
+            require!(job.status != JobStatus::Closed, ErrorCodes::JobClosed);
+            require!(job.status != JobStatus::Terminated, ErrorCodes::JobTerminating);
+            require!(job.status != JobStatus::Closing, ErrorCodes::JobClosing);
 
             let last_settled = job.last_settled;
             let current_time = Clock::get()?.unix_timestamp as u64;
@@ -593,13 +2085,15 @@ pub mod market_v {
                     token_program,
                     token_mint,
                     program_token_account,
-                    provider_token_account,
+                    payouts,
                     credit_mint,
                     program_credit_token_account,
                     signer_seeds,
                     signer,
                     state,
                     credit_program_usdc_token_account,
+                    credit_program_reserve,
+                    credit_program_provider_tier,
                     credit_program,
                 )?;
                 require!(res, ErrorCodes::InsufficientFundsToReviseRate);
@@ -621,13 +2115,15 @@ pub mod market_v {
                 token_program,
                 token_mint,
                 program_token_account,
-                provider_token_account,
+                payouts,
                 credit_mint,
                 program_credit_token_account,
                 signer_seeds,
                 signer,
                 state,
                 credit_program_usdc_token_account,
+                credit_program_reserve,
+                credit_program_provider_tier,
                 credit_program,
             )?;
             require!(res, ErrorCodes::InsufficientFundsToReviseRate);
@@ -642,21 +2138,29 @@ pub mod market_v {
             token_program: &Program<'info, Token>,
             token_mint: &Account<'info, Mint>,
             program_token_account: &mut Account<'info, TokenAccount>,
-            provider_token_account: &mut Account<'info, TokenAccount>,
+            payouts: &[Payout<'info>],
             credit_mint: &Account<'info, Mint>,
             program_credit_token_account: &mut Account<'info, TokenAccount>,
             signer_seeds: &[&[&[u8]]],
             signer: &Signer<'info>,
             state: &mut UncheckedAccount<'info>,
             credit_program_usdc_token_account: &mut UncheckedAccount<'info>,
+            credit_program_reserve: &mut UncheckedAccount<'info>,
+            credit_program_provider_tier: &UncheckedAccount<'info>,
             credit_program: &Program<'info, OysterCredits>,
         ) -> Result<bool> {
+            // Pending jobs are not billed until accepted
+            if job.status == JobStatus::Pending {
+                return Ok(true);
+            }
+
             let last_settled = job.last_settled;
+            let settle_till = billed_till(job, settle_till);
 
-            if settle_till == last_settled {
+            // Time up to `last_settled` is already paid for
+            if settle_till <= last_settled {
                 return Ok(true);
             }
-            require!(settle_till > last_settled, ErrorCodes::CannotSettle);
 
             let usage_duration = settle_till - last_settled;
             let amount_used = calculate_amount_used(rate, usage_duration);
@@ -666,7 +2170,7 @@ pub mod market_v {
                 job,
                 token_mint,
                 program_token_account,
-                provider_token_account,
+                payouts,
                 credit_mint,
                 program_credit_token_account,
                 token_program,
@@ -675,6 +2179,8 @@ pub mod market_v {
                 signer,
                 state,
                 credit_program_usdc_token_account,
+                credit_program_reserve,
+                credit_program_provider_tier,
                 credit_program,
             )?;
 
@@ -689,15 +2195,89 @@ pub mod market_v {
             Ok(amount_used <= settle_amount)
         }
 
-        fn calculate_amount_used(rate: u64, usage_duration: u64) -> u64 {
-            (rate * usage_duration + 10u64.pow(EXTRA_DECIMALS as u32) - 1) / 10u64.pow(EXTRA_DECIMALS as u32)
+        // Billing stops once a close request or provider termination takes effect
+        pub fn billed_till(job: &Job, settle_till: u64) -> u64 {
+            if job.end_at != 0 {
+                settle_till.min(job.end_at.max(job.last_settled))
+            } else {
+                settle_till
+            }
+        }
+
+        // Amount settling the job at `now` would pay the provider
+        pub fn amount_due(job: &Job, now: u64) -> u64 {
+            if job.status == JobStatus::Pending {
+                return 0;
+            }
+
+            let settle_till = billed_till(job, now);
+            if settle_till <= job.last_settled {
+                return 0;
+            }
+            calculate_amount_used(job.rate, settle_till - job.last_settled).min(job.balance)
+        }
+
+        // Emit `JobLowBalance` when an active job's runway drops below its threshold or the
+        // notice period. Only crossings are reported, recovering re-arms the warning.
+        pub fn check_low_balance(job: &mut Account<Job>, now: u64, notice_period: u64) {
+            if job.status != JobStatus::Active {
+                return;
+            }
+
+            let runway = job_runway(job, now);
+            let level = if runway < notice_period {
+                LowBalanceLevel::BelowNotice
+            } else if runway < job.runway_threshold {
+                LowBalanceLevel::BelowThreshold
+            } else {
+                LowBalanceLevel::None
+            };
+
+            if level > job.low_balance_level {
+                emit!(JobLowBalance {
+                    job: job.key(),
+                    runway,
+                    runway_threshold: job.runway_threshold,
+                    below_notice: level == LowBalanceLevel::BelowNotice,
+                });
+            }
+            job.low_balance_level = level;
+        }
+
+        // Seconds from `now` the balance left after settling pays for
+        pub fn job_runway(job: &Job, now: u64) -> u64 {
+            if job.rate == 0 {
+                return u64::MAX;
+            }
+
+            let remaining = job.balance - amount_due(job, now);
+            let paid_from = if job.status == JobStatus::Pending {
+                now
+            } else {
+                job.last_settled.max(now)
+            };
+            let paid_for = (remaining as u128 * 10u128.pow(EXTRA_DECIMALS as u32) / job.rate as u128)
+                .min(u64::MAX as u128) as u64;
+            let mut paid_until = paid_from.saturating_add(paid_for);
+            if job.end_at != 0 {
+                paid_until = paid_until.min(job.end_at);
+            }
+            paid_until.saturating_sub(now)
+        }
+
+        // Amount billed for `usage_duration` at `rate`, rounded up. Saturates at u64::MAX, which
+        // callers cap at the job balance.
+        pub fn calculate_amount_used(rate: u64, usage_duration: u64) -> u64 {
+            let scale = 10u128.pow(EXTRA_DECIMALS as u32);
+            let amount = (rate as u128 * usage_duration as u128).div_ceil(scale);
+            u64::try_from(amount).unwrap_or(u64::MAX)
         }
 
         pub fn settle_tokens<'info>(
             job: &mut Account<'info, Job>,
             token_mint: &Account<'info, Mint>,
             program_token_account: &mut Account<'info, TokenAccount>,
-            provider_token_account: &Account<'info, TokenAccount>,
+            payouts: &[Payout<'info>],
             credit_mint: &Account<'info, Mint>,
             program_credit_token_account: &mut Account<'info, TokenAccount>,
             token_program: &Program<'info, Token>,
@@ -706,6 +2286,8 @@ pub mod market_v {
             signer: &Signer<'info>,
             state: &mut UncheckedAccount<'info>,
             credit_program_usdc_token_account: &mut UncheckedAccount<'info>,
+            credit_program_reserve: &mut UncheckedAccount<'info>,
+            credit_program_provider_tier: &UncheckedAccount<'info>,
             credit_program: &Program<'info, OysterCredits>,
         ) -> Result<()> {
             // Deduct the amount from the job's balance
@@ -725,68 +2307,624 @@ pub mod market_v {
                     // Deduct the credit amount from the job's credit balance
                     job.credit_balance -= credit_amount;
 
-                    // TODO: add cpi call to credit program
-                    // Perform a CPI call to the redeem_and_burn instruction in the oyster-credits program
-                    let cpi_program = credit_mint.to_account_info();
-                    let cpi_ctx = CpiContext::new(
-                        credit_program.to_account_info(),  // oyster_credits program ID
-                        RedeemAndBurn { 
-                            state: state.to_account_info(),
-                            signer: signer.to_account_info(),
-                            usdc_mint: token_mint.to_account_info(),
-                            program_usdc_token_account: credit_program_usdc_token_account.to_account_info(),
-                            user_usdc_token_account: provider_token_account.to_account_info(),
-                            credit_mint: credit_mint.to_account_info(),
-                            market_program_credit_token_account: program_credit_token_account.to_account_info(),
-                            // user_credit_token_account: ,
-                            token_program: token_program.to_account_info(),
-                            system_program: credit_program.to_account_info()
+                    // Credits are redeemed out of the market credit account, which signs for the burn
+                    let credit_mint_key = credit_mint.key();
+                    let (_, credit_bump) = Pubkey::find_program_address(&[b"credit_token", credit_mint_key.as_ref()], &crate::ID);
+                    let credit_signer_seeds: &[&[&[u8]]] = &[&[b"credit_token", credit_mint_key.as_ref(), &[credit_bump]]];
+
+                    let credit_shares = split_by_share(credit_amount, payouts);
+                    for (payout, share) in payouts.iter().zip(credit_shares) {
+                        if share == 0 {
+                            continue;
                         }
-                    );
-                    oyster_credits::cpi::redeem_and_burn(cpi_ctx, credit_amount)?;
+
+                        // Perform a CPI call to the redeem_and_burn instruction in the oyster-credits program
+                        let cpi_ctx = CpiContext::new(
+                            credit_program.to_account_info(),  // oyster_credits program ID
+                            RedeemAndBurn { 
+                                state: state.to_account_info(),
+                                signer: signer.to_account_info(),
+                                usdc_mint: token_mint.to_account_info(),
+                                reserve: credit_program_reserve.to_account_info(),
+                                program_usdc_token_account: credit_program_usdc_token_account.to_account_info(),
+                                user_usdc_token_account: payout.token_account.clone(),
+                                provider_tier: credit_program_provider_tier.to_account_info(),
+                                credit_mint: credit_mint.to_account_info(),
+                                market_program_credit_token_account: program_credit_token_account.to_account_info(),
+                                // user_credit_token_account: ,
+                                token_program: token_program.to_account_info(),
+                            }
+                        ).with_signer(credit_signer_seeds);
+                        // Credits may be redeemed below face value depending on the provider's tier
+                        let paid_amount = oyster_credits::cpi::redeem_and_burn(
+                            cpi_ctx,
+                            share,
+                            job.key(),
+                            job.provider,
+                        )?.get();
+
+                        emit!(JobSettlementWithdrawn {
+                            job: job.key(),
+                            token: credit_mint.key(),
+                            provider: payout.owner,
+                            face_amount: share,
+                            paid_amount,
+                        });
+                    }
+                }
+            }
+
+            if token_amount > 0 {
+                let token_shares = split_by_share(token_amount, payouts);
+                for (payout, share) in payouts.iter().zip(token_shares) {
+                    if share == 0 {
+                        continue;
+                    }
+
+                    // Transfer tokens to the provider
+                    let cpi_accounts = Transfer {
+                        from: program_token_account.to_account_info(),
+                        to: payout.token_account.clone(),
+                        authority: program_token_account.to_account_info(),
+                    };
+                    let cpi_ctx = CpiContext::new(
+                        token_program.to_account_info(),
+                        cpi_accounts
+                    ).with_signer(signer_seeds);
+                    token::transfer(cpi_ctx, share)?;
 
                     emit!(JobSettlementWithdrawn {
                         job: job.key(),
-                        token: credit_mint.key(),
-                        provider: provider_token_account.owner,
-                        amount: credit_amount,
+                        token: program_token_account.mint,
+                        provider: payout.owner,
+                        face_amount: share,
+                        paid_amount: share,
                     });
                 }
             }
 
-            if token_amount > 0 {
-                // Transfer tokens to the provider
-                let cpi_accounts = Transfer {
-                    from: program_token_account.to_account_info(),
-                    to: provider_token_account.to_account_info(),
-                    authority: program_token_account.to_account_info(),
-                };
+            // Usage is paid from the owner's own funds first, sponsors only back what is left
+            job.sponsored_balance = job.sponsored_balance.min(job.balance - job.credit_balance);
+
+            Ok(())
+        }
+
+        // Split `amount` by basis-point shares, the last payout taking the rounding remainder
+        fn split_by_share(amount: u64, payouts: &[Payout]) -> Vec<u64> {
+            let mut remaining = amount;
+            payouts
+                .iter()
+                .enumerate()
+                .map(|(i, payout)| {
+                    let share = if i + 1 == payouts.len() {
+                        remaining
+                    } else {
+                        ((amount as u128) * (payout.share_bps as u128) / (BPS_DENOMINATOR as u128)) as u64
+                    };
+                    remaining -= share;
+                    share
+                })
+                .collect()
+        }
+
+        // Move a job to a new status
+        pub fn set_job_status(job: &mut Account<Job>, status: JobStatus) {
+            let old_status = job.status;
+            job.status = status;
+
+            emit!(JobStatusChanged {
+                job: job.key(),
+                old_status,
+                new_status: status,
+            });
+        }
+
+        // Resize a program account, taking any extra rent from `payer` and refunding any excess
+        pub fn resize_account<'info>(
+            account: &AccountInfo<'info>,
+            space: usize,
+            payer: &Signer<'info>,
+            system_program: &Program<'info, System>,
+        ) -> Result<()> {
+            let rent_minimum = Rent::get()?.minimum_balance(space);
+            let lamports = account.lamports();
+            if rent_minimum > lamports {
                 let cpi_ctx = CpiContext::new(
-                    token_program.to_account_info(),
-                    cpi_accounts
-                ).with_signer(signer_seeds);
-                token::transfer(cpi_ctx, token_amount)?;
+                    system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: payer.to_account_info(),
+                        to: account.clone(),
+                    },
+                );
+                anchor_lang::system_program::transfer(cpi_ctx, rent_minimum - lamports)?;
+            } else {
+                let excess = lamports - rent_minimum;
+                **account.lamports.borrow_mut() -= excess;
+                **payer.to_account_info().lamports.borrow_mut() += excess;
+            }
 
-                emit!(JobSettlementWithdrawn {
-                    job: job.key(),
-                    token: program_token_account.mint,
-                    provider: provider_token_account.owner,
-                    amount: token_amount,
-                });
+            account.realloc(space, false)?;
+            Ok(())
+        }
+
+        // Close a program account that may not have been created, sending its rent to `destination`
+        pub fn close_if_exists<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+            if account.owner != &crate::ID || account.data_is_empty() {
+                return Ok(());
             }
 
+            **destination.lamports.borrow_mut() = destination.lamports()
+                .checked_add(account.lamports())
+                .ok_or(ErrorCodes::InvalidAmount)?;
+            **account.lamports.borrow_mut() = 0;
+            account.assign(&System::id());
+            account.realloc(0, false)?;
             Ok(())
         }
 
-        pub fn deposit_token<'info>(
-            job: &mut Account<'info, Job>,
-            credit_mint: &mut Account<'info, Mint>,
-            user_credit_token_account: &mut Account<'info, TokenAccount>,
-            program_credit_token_account: &mut Account<'info, TokenAccount>,
-            token_mint: &mut Account<'info, Mint>,
-            user_token_account: &mut Account<'info, TokenAccount>,
+        // The NFT of a closed job no longer represents anything. The holder's NFT is burned when
+        // they close the job, and frozen for good otherwise since only the holder can burn it.
+        pub fn retire_job_nft<'info>(
+            job: &Account<'info, Job>,
+            job_bump: u8,
+            job_nft_mint: Option<&Account<'info, Mint>>,
+            job_nft_account: Option<&Account<'info, TokenAccount>>,
+            authority: &AccountInfo<'info>,
+            token_program: &Program<'info, Token>,
+        ) -> Result<()> {
+            if job.nft_mint == Pubkey::default() {
+                return Ok(());
+            }
+            let (Some(job_nft_mint), Some(job_nft_account)) = (job_nft_mint, job_nft_account) else {
+                return err!(ErrorCodes::JobNftRequired);
+            };
+            require_keys_eq!(job_nft_mint.key(), job.nft_mint, ErrorCodes::InvalidMint);
+
+            if job_nft_account.owner == authority.key() {
+                let cpi_accounts = Burn {
+                    mint: job_nft_mint.to_account_info(),
+                    from: job_nft_account.to_account_info(),
+                    authority: authority.clone(),
+                };
+                let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+                return token::burn(cpi_ctx, 1);
+            }
+
+            let job_index = job.index.to_le_bytes();
+            let seeds: &[&[u8]] = &[b"job", job_index.as_ref(), &[job_bump]];
+            let signer_seeds: &[&[&[u8]]] = &[seeds];
+            let cpi_accounts = FreezeAccount {
+                account: job_nft_account.to_account_info(),
+                mint: job_nft_mint.to_account_info(),
+                authority: job.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts).with_signer(signer_seeds);
+            token::freeze_account(cpi_ctx)
+        }
+
+        // Close a finished job and refund its rent to the owner. Markets keeping tombstones shrink
+        // the account to a closed record instead of deleting it.
+        pub fn close_job<'info>(
+            job: &mut Account<'info, Job>,
+            owner: AccountInfo<'info>,
+            tombstone: bool,
+        ) -> Result<()> {
+            set_job_status(job, JobStatus::Closed);
+            if !tombstone {
+                return job.close(owner);
+            }
+
+            job.metadata = String::new();
+            job.provider_cp = String::new();
+
+            let job_info = job.to_account_info();
+            job_info.realloc(Job::TOMBSTONE_SPACE, false)?;
+            let rent_exempt = Rent::get()?.minimum_balance(Job::TOMBSTONE_SPACE);
+            let excess = job_info.lamports().saturating_sub(rent_exempt);
+            **job_info.try_borrow_mut_lamports()? -= excess;
+            **owner.try_borrow_mut_lamports()? += excess;
+
+            Ok(())
+        }
+
+        // Settle a closing or terminated job up to the end of its notice, refund the rest to the
+        // owner and close it. Either side of the job can finalize.
+        pub fn finalize_job<'info>(
+            accounts: &mut JobFinalize<'info>,
+            bumps: &JobFinalizeBumps,
+            remaining_accounts: &[AccountInfo<'info>],
+        ) -> Result<u64> {
+            let authority = *accounts.authority.key;
+            let owner = job_owner(&accounts.job, accounts.job_nft_account.as_deref())?;
+            require!(
+                authority == owner || authority == accounts.provider_account.owner,
+                ErrorCodes::Unauthorized
+            );
+
+            let end_at = accounts.job.end_at;
+            let current_time = Clock::get()?.unix_timestamp as u64;
+            require!(current_time >= end_at, ErrorCodes::NoticePeriodNotOver);
+
+            let (refunded, _) = wind_up_job(accounts, bumps, remaining_accounts, end_at, None)?;
+            Ok(refunded)
+        }
+
+        // Settle a job up to `settle_till`, refund the rest to the owner, release it from the
+        // provider and close it. When reaping, the job must no longer cover its notice and the
+        // caller is paid up to `reap_bounty` lamports out of the job rent. Returns the refunded
+        // balance and the bounty paid.
+        pub fn wind_up_job<'info>(
+            accounts: &mut JobFinalize<'info>,
+            bumps: &JobFinalizeBumps,
+            remaining_accounts: &[AccountInfo<'info>],
+            settle_till: u64,
+            reap_bounty: Option<u64>,
+        ) -> Result<(u64, u64)> {
+            require_keys_eq!(accounts.token_mint.key(), accounts.market.token_mint, ErrorCodes::InvalidMint);
+            resolve_job_owner(
+                &mut accounts.job,
+                accounts.job_nft_account.as_deref(),
+                &accounts.owner,
+                &accounts.user_token_account,
+                &accounts.user_credit_token_account,
+            )?;
+
+            let token_mint_key = accounts.token_mint.key();
+            let seeds: &[&[u8]] = &[b"job_token", token_mint_key.as_ref(), &[bumps.program_token_account]];
+            let token_signer_seeds: &[&[&[u8]]] = &[seeds];
+
+            let payouts = provider_payouts(
+                &accounts.provider_account,
+                &accounts.provider_token_account,
+                &accounts.token_mint,
+                remaining_accounts,
+            )?;
+            let job_rate = accounts.job.rate;
+            job_settle_internal(
+                &mut accounts.job,
+                job_rate,
+                settle_till,
+                &accounts.token_program,
+                &accounts.token_mint,
+                &mut accounts.program_token_account,
+                &payouts,
+                &accounts.credit_mint,
+                &mut accounts.program_credit_token_account,
+                token_signer_seeds,
+                &accounts.authority,
+                &mut accounts.state,
+                &mut accounts.credit_program_usdc_token_account,
+                &mut accounts.credit_program_reserve,
+                &accounts.credit_program_provider_tier,
+                &accounts.credit_program,
+            )?;
+
+            if reap_bounty.is_some() {
+                let job = &accounts.job;
+                let reserve = notice_reserve(job, settle_till, accounts.market.notice_period);
+                require!(job.balance == 0 || job.balance < reserve, ErrorCodes::JobNotReapable);
+            }
+
+            // Sponsors follow the beneficiary accounts
+            refund_sponsors(
+                &mut accounts.job,
+                &remaining_accounts[accounts.provider_account.beneficiaries.len()..],
+                &accounts.token_mint,
+                &accounts.program_token_account,
+                &accounts.token_program,
+                token_signer_seeds,
+            )?;
+
+            let balance = accounts.job.balance;
+            if balance > 0 {
+                let credit_mint_key = accounts.credit_mint.key();
+                let seeds: &[&[u8]] = &[b"credit_token", credit_mint_key.as_ref(), &[bumps.program_credit_token_account]];
+                let credit_signer_seeds: &[&[&[u8]]] = &[seeds];
+
+                withdraw_internal(
+                    &mut accounts.job,
+                    &accounts.token_mint,
+                    &mut accounts.program_token_account,
+                    &accounts.credit_mint,
+                    &mut accounts.program_credit_token_account,
+                    &mut accounts.user_credit_token_account,
+                    &accounts.token_program,
+                    &accounts.user_token_account,
+                    accounts.market.credit_refund_policy,
+                    accounts.issuer_credit_token_account.as_deref(),
+                    balance,
+                    token_signer_seeds,
+                    credit_signer_seeds,
+                    &accounts.state,
+                    &accounts.credit_program,
+                )?;
+            }
+
+            let provider_account = &mut accounts.provider_account;
+            provider_account.active_jobs = provider_account.active_jobs.checked_sub(1).ok_or(ErrorCodes::ActiveJobsUnderflow)?;
+
+            // The bounty comes out of the rent, leaving a tombstone rent exempt
+            let mut bounty = 0;
+            if let Some(reap_bounty) = reap_bounty {
+                let job_info = accounts.job.to_account_info();
+                let kept = if accounts.market.tombstone_jobs {
+                    Rent::get()?.minimum_balance(Job::TOMBSTONE_SPACE)
+                } else {
+                    0
+                };
+                bounty = reap_bounty.min(job_info.lamports().saturating_sub(kept));
+                **job_info.try_borrow_mut_lamports()? -= bounty;
+                **accounts.authority.to_account_info().try_borrow_mut_lamports()? += bounty;
+            }
+
+            retire_job_nft(
+                &accounts.job,
+                bumps.job,
+                accounts.job_nft_mint.as_deref(),
+                accounts.job_nft_account.as_deref(),
+                &accounts.authority.to_account_info(),
+                &accounts.token_program,
+            )?;
+            close_if_exists(&accounts.topup_policy, &accounts.owner.to_account_info())?;
+            close_job(&mut accounts.job, accounts.owner.to_account_info(), accounts.market.tombstone_jobs)?;
+
+            Ok((balance, bounty))
+        }
+
+        // Amount a job must keep to pay for the notice period from `now`, up to the end of billing
+        pub fn notice_reserve(job: &Job, now: u64, notice_period: u64) -> u64 {
+            if job.status == JobStatus::Pending {
+                return 0;
+            }
+
+            let mut billed_till = now + notice_period;
+            if job.end_at != 0 {
+                billed_till = billed_till.min(job.end_at);
+            }
+            calculate_amount_used(job.rate, billed_till.saturating_sub(job.last_settled.max(now)))
+        }
+
+        // Share of the unused funds owed to `sponsorship` once `owed` is paid for. Sponsors are
+        // backed by the tokens left after usage, which credits pay for first.
+        pub fn sponsor_refund_amount(job: &Job, sponsorship: &JobSponsorship, owed: u64) -> u64 {
+            let token_balance = job.balance - job.credit_balance;
+            let backing = job.sponsored_balance.min(token_balance.saturating_sub(owed.saturating_sub(job.credit_balance)));
+            match job.sponsor_refund_policy {
+                SponsorRefundPolicy::ProRata => {
+                    (sponsorship.amount as u128 * backing as u128 / job.sponsor_claims as u128) as u64
+                }
+                SponsorRefundPolicy::Lifo => sponsorship.amount.min(backing),
+            }
+        }
+
+        // Drop a refunded sponsorship from the job's accounting
+        pub fn release_sponsorship(job: &mut Job, sponsorship: &JobSponsorship, refund: u64) {
+            job.balance -= refund;
+            job.sponsored_balance -= refund;
+            job.sponsor_claims -= sponsorship.amount;
+            job.sponsors_outstanding -= 1;
+            // Lifo refunds pop the latest sponsorship so its seed can be reused
+            if job.sponsor_refund_policy == SponsorRefundPolicy::Lifo {
+                job.sponsor_seq -= 1;
+            }
+        }
+
+        // Refund every outstanding sponsorship of a job that is being closed and has paid for its
+        // usage. Each sponsorship is passed as a (sponsorship, sponsor token account, sponsor)
+        // triple, latest first for Lifo jobs, and is closed to the sponsor.
+        pub fn refund_sponsors<'info>(
+            job: &mut Account<'info, Job>,
+            sponsor_accounts: &[AccountInfo<'info>],
+            token_mint: &Account<'info, Mint>,
+            program_token_account: &Account<'info, TokenAccount>,
+            token_program: &Program<'info, Token>,
+            token_signer_seeds: &[&[&[u8]]],
+        ) -> Result<()> {
+            require!(
+                sponsor_accounts.len() == job.sponsors_outstanding as usize * 3,
+                ErrorCodes::SponsorsOutstanding
+            );
+
+            for accounts in sponsor_accounts.chunks_exact(3) {
+                let (sponsorship_info, sponsor_token_info, sponsor_info) = (&accounts[0], &accounts[1], &accounts[2]);
+
+                require!(sponsorship_info.owner == &crate::ID, ErrorCodes::InvalidSponsorAccount);
+                let sponsorship = JobSponsorship::try_deserialize(&mut &sponsorship_info.data.borrow()[..])?;
+                require!(
+                    sponsorship.job == job.key() && sponsor_info.key() == sponsorship.sponsor,
+                    ErrorCodes::InvalidSponsorAccount
+                );
+                if job.sponsor_refund_policy == SponsorRefundPolicy::Lifo {
+                    require!(sponsorship.seq + 1 == job.sponsor_seq, ErrorCodes::SponsorRefundOrder);
+                }
+
+                let refund = sponsor_refund_amount(job, &sponsorship, 0);
+                if refund > 0 {
+                    require!(sponsor_token_info.owner == &token::ID, ErrorCodes::InvalidSponsorAccount);
+                    let sponsor_token_account = TokenAccount::try_deserialize(&mut &sponsor_token_info.data.borrow()[..])?;
+                    require!(
+                        sponsor_token_account.owner == sponsorship.sponsor
+                            && sponsor_token_account.mint == token_mint.key(),
+                        ErrorCodes::InvalidSponsorAccount
+                    );
+
+                    let cpi_accounts = Transfer {
+                        from: program_token_account.to_account_info(),
+                        to: sponsor_token_info.clone(),
+                        authority: program_token_account.to_account_info(),
+                    };
+                    let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts)
+                        .with_signer(token_signer_seeds);
+                    token::transfer(cpi_ctx, refund)?;
+                }
+
+                release_sponsorship(job, &sponsorship, refund);
+                close_if_exists(sponsorship_info, sponsor_info)?;
+
+                emit!(JobSponsorRefunded {
+                    job: job.key(),
+                    sponsor: sponsorship.sponsor,
+                    seq: sponsorship.seq,
+                    amount: refund,
+                });
+            }
+
+            Ok(())
+        }
+
+        // Refund a pending job's full balance to its sponsors, passed as remaining accounts, and
+        // owner, release it from the provider and close the job
+        pub fn cancel_pending_job<'info>(
+            accounts: &mut JobCancelPending<'info>,
+            bumps: &JobCancelPendingBumps,
+            remaining_accounts: &[AccountInfo<'info>],
+        ) -> Result<u64> {
+            require!(accounts.job.status == JobStatus::Pending, ErrorCodes::JobNotPending);
+            resolve_job_owner(
+                &mut accounts.job,
+                accounts.job_nft_account.as_deref(),
+                &accounts.owner,
+                &accounts.user_token_account,
+                &accounts.user_credit_token_account,
+            )?;
+
+            let token_mint_key = accounts.token_mint.key();
+            let seeds: &[&[u8]] = &[b"job_token", token_mint_key.as_ref(), &[bumps.program_token_account]];
+            let token_signer_seeds: &[&[&[u8]]] = &[seeds];
+            let credit_mint_key = accounts.credit_mint.key();
+            let seeds: &[&[u8]] = &[b"credit_token", credit_mint_key.as_ref(), &[bumps.program_credit_token_account]];
+            let credit_signer_seeds: &[&[&[u8]]] = &[seeds];
+
+            refund_sponsors(
+                &mut accounts.job,
+                remaining_accounts,
+                &accounts.token_mint,
+                &accounts.program_token_account,
+                &accounts.token_program,
+                token_signer_seeds,
+            )?;
+
+            let balance = accounts.job.balance;
+            if balance > 0 {
+                withdraw_internal(
+                    &mut accounts.job,
+                    &accounts.token_mint,
+                    &mut accounts.program_token_account,
+                    &accounts.credit_mint,
+                    &mut accounts.program_credit_token_account,
+                    &mut accounts.user_credit_token_account,
+                    &accounts.token_program,
+                    &accounts.user_token_account,
+                    accounts.market.credit_refund_policy,
+                    accounts.issuer_credit_token_account.as_deref(),
+                    balance,
+                    token_signer_seeds,
+                    credit_signer_seeds,
+                    &accounts.state,
+                    &accounts.credit_program,
+                )?;
+            }
+
+            let provider_account = &mut accounts.provider_account;
+            provider_account.active_jobs = provider_account.active_jobs.checked_sub(1).ok_or(ErrorCodes::ActiveJobsUnderflow)?;
+
+            retire_job_nft(
+                &accounts.job,
+                bumps.job,
+                accounts.job_nft_mint.as_deref(),
+                accounts.job_nft_account.as_deref(),
+                &accounts.authority.to_account_info(),
+                &accounts.token_program,
+            )?;
+            close_if_exists(&accounts.topup_policy, &accounts.owner.to_account_info())?;
+            close_job(&mut accounts.job, accounts.owner.to_account_info(), accounts.market.tombstone_jobs)?;
+
+            Ok(balance)
+        }
+
+        // Require `rate` to meet the provider's rate card entry for the instance type and region in
+        // the job metadata. Providers without a card accept any rate.
+        pub fn require_rate_card(
+            provider: &Account<Provider>,
+            rate_card: Option<&Account<RateCard>>,
+            metadata: &str,
+            rate: u64,
+            now: i64,
+        ) -> Result<()> {
+            if !provider.has_rate_card {
+                return Ok(());
+            }
+            let rate_card = rate_card.ok_or(ErrorCodes::RateCardRequired)?;
+
+            let instance_type = metadata_field(metadata, "instance").ok_or(ErrorCodes::RateCardEntryNotFound)?;
+            let region = metadata_field(metadata, "region").unwrap_or_default();
+            let entry = rate_card
+                .active_entries(now)
+                .iter()
+                .find(|entry| entry.instance_type == instance_type && (entry.region.is_empty() || entry.region == region))
+                .ok_or(ErrorCodes::RateCardEntryNotFound)?;
+
+            require!(rate >= entry.min_rate, ErrorCodes::RateBelowRateCard);
+            Ok(())
+        }
+
+        // Read a top-level string field of JSON job metadata, e.g. `"instance": "c6a.xlarge"`
+        pub fn metadata_field(metadata: &str, key: &str) -> Option<String> {
+            let metadata: serde_json::Value = serde_json::from_str(metadata).ok()?;
+            metadata.get(key)?.as_str().map(str::to_string)
+        }
+
+        // Resolve where a provider's earnings go: the token accounts of its beneficiaries, passed
+        // in order as remaining accounts, or the provider's own token account
+        pub fn provider_payouts<'info>(
+            provider: &Account<'info, Provider>,
+            provider_token_account: &Account<'info, TokenAccount>,
+            token_mint: &Account<'info, Mint>,
+            remaining_accounts: &[AccountInfo<'info>],
+        ) -> Result<Vec<Payout<'info>>> {
+            if provider.beneficiaries.is_empty() {
+                return Ok(vec![Payout {
+                    token_account: provider_token_account.to_account_info(),
+                    owner: provider_token_account.owner,
+                    share_bps: BPS_DENOMINATOR,
+                }]);
+            }
+
+            require!(
+                remaining_accounts.len() >= provider.beneficiaries.len(),
+                ErrorCodes::InvalidBeneficiaryAccount
+            );
+
+            provider.beneficiaries
+                .iter()
+                .zip(remaining_accounts)
+                .map(|(beneficiary, account_info)| {
+                    require!(account_info.owner == &token::ID, ErrorCodes::InvalidBeneficiaryAccount);
+                    let token_account = TokenAccount::try_deserialize(&mut &account_info.data.borrow()[..])?;
+                    require!(
+                        token_account.owner == beneficiary.wallet && token_account.mint == token_mint.key(),
+                        ErrorCodes::InvalidBeneficiaryAccount
+                    );
+
+                    Ok(Payout {
+                        token_account: account_info.clone(),
+                        owner: beneficiary.wallet,
+                        share_bps: beneficiary.share_bps,
+                    })
+                })
+                .collect()
+        }
+
+        pub fn deposit_token<'info>(
+            job: &mut Account<'info, Job>,
+            credit_mint: &mut Account<'info, Mint>,
+            user_credit_token_account: &mut Account<'info, TokenAccount>,
+            program_credit_token_account: &mut Account<'info, TokenAccount>,
+            token_mint: &mut Account<'info, Mint>,
+            user_token_account: &mut Account<'info, TokenAccount>,
             program_token_account: &mut Account<'info, TokenAccount>,
-            signer: &Signer<'info>,
+            authority: &AccountInfo<'info>,  // Owner, or a delegate signing with `signer_seeds`
+            signer_seeds: &[&[&[u8]]],
             token_program: &Program<'info, Token>,
             // job_index: u128,
             amount: u64
@@ -794,7 +2932,11 @@ pub mod market_v {
             let mut token_amount = amount;
             let mut credit_amount = 0;
     
-            if credit_mint.key() != Pubkey::default() {
+            // Credits are only pulled from accounts the authority can move
+            let can_move_credits = user_credit_token_account.owner == authority.key()
+                || user_credit_token_account.delegate == COption::Some(authority.key());
+
+            if credit_mint.key() != Pubkey::default() && can_move_credits {
                 // Get the credit token balance and allowance (TODO: check delegate)
                 let credit_balance = user_credit_token_account.amount
                     .min(user_credit_token_account.delegated_amount);
@@ -807,12 +2949,12 @@ pub mod market_v {
                     let cpi_accounts = Transfer {
                         from: user_credit_token_account.to_account_info(),
                         to: program_credit_token_account.to_account_info(),
-                        authority: signer.to_account_info(),
+                        authority: authority.clone(),
                     };
                     let cpi_ctx = CpiContext::new(
                         token_program.to_account_info(),
                         cpi_accounts
-                    );
+                    ).with_signer(signer_seeds);
                     token::transfer(cpi_ctx, credit_amount)?;
     
                     // Update job credit balance
@@ -820,7 +2962,7 @@ pub mod market_v {
     
                     emit!(JobDeposited {
                         job: job.key(),
-                        from: signer.key(),
+                        from: user_credit_token_account.owner,
                         amount: credit_amount,
                     });
                 }
@@ -831,17 +2973,17 @@ pub mod market_v {
                 let cpi_accounts = Transfer {
                     from: user_token_account.to_account_info(),
                     to: program_token_account.to_account_info(),
-                    authority: signer.to_account_info(),
+                    authority: authority.clone(),
                 };
                 let cpi_ctx = CpiContext::new(
                     token_program.to_account_info(),
                     cpi_accounts
-                );
+                ).with_signer(signer_seeds);
                 token::transfer(cpi_ctx, token_amount)?;
     
                 emit!(JobDeposited {
                     job: job.key(),
-                    from: signer.key(),
+                    from: user_token_account.owner,
                     amount: token_amount,
                 });
             }
@@ -876,9 +3018,13 @@ pub mod market_v {
             user_credit_token_account: &mut Account<'info, TokenAccount>,
             token_program: &Program<'info, Token>,
             user_token_account: &Account<'info, TokenAccount>,
+            credit_refund_policy: CreditRefundPolicy,
+            issuer_credit_token_account: Option<&Account<'info, TokenAccount>>,
             amount: u64,
             token_signer_seeds: &[&[&[u8]]],
             credit_signer_seeds: &[&[&[u8]]],
+            state: &UncheckedAccount<'info>,
+            credit_program: &Program<'info, OysterCredits>,
         ) -> Result<()> {
             let job_balance = job.balance;
             require!(job_balance >= amount, ErrorCodes::InsufficientBalance);
@@ -927,24 +3073,105 @@ pub mod market_v {
 
                 job.credit_balance -= withdraw_amount;
 
-                let cpi_accounts = Transfer {
-                    from: program_credit_token_account.to_account_info(),
-                    to: user_credit_token_account.to_account_info(),
-                    authority: program_credit_token_account.to_account_info(),
-                };
-                let cpi_ctx = CpiContext::new(
-                    token_program.to_account_info(),
-                    cpi_accounts,
-                )
-                .with_signer(credit_signer_seeds);
-                token::transfer(cpi_ctx, withdraw_amount)?;
+                refund_credits(
+                    job,
+                    credit_mint,
+                    program_credit_token_account,
+                    user_credit_token_account,
+                    token_program,
+                    credit_refund_policy,
+                    issuer_credit_token_account,
+                    withdraw_amount,
+                    credit_signer_seeds,
+                    state,
+                    credit_program,
+                )?;
+            }
 
-                emit!(JobWithdrew {
-                    job: job.key(),
-                    to: user_credit_token_account.owner,
-                    token: credit_mint.key(),
-                    amount: withdraw_amount,
-                });
+            Ok(())
+        }
+
+        // Send unused credits to the destination set by the market's refund policy
+        fn refund_credits<'info>(
+            job: &Account<'info, Job>,
+            credit_mint: &Account<'info, Mint>,
+            program_credit_token_account: &Account<'info, TokenAccount>,
+            user_credit_token_account: &Account<'info, TokenAccount>,
+            token_program: &Program<'info, Token>,
+            credit_refund_policy: CreditRefundPolicy,
+            issuer_credit_token_account: Option<&Account<'info, TokenAccount>>,
+            amount: u64,
+            credit_signer_seeds: &[&[&[u8]]],
+            state: &UncheckedAccount<'info>,
+            credit_program: &Program<'info, OysterCredits>,
+        ) -> Result<()> {
+            match credit_refund_policy {
+                CreditRefundPolicy::User => {
+                    let cpi_accounts = Transfer {
+                        from: program_credit_token_account.to_account_info(),
+                        to: user_credit_token_account.to_account_info(),
+                        authority: program_credit_token_account.to_account_info(),
+                    };
+                    let cpi_ctx = CpiContext::new(
+                        token_program.to_account_info(),
+                        cpi_accounts,
+                    )
+                    .with_signer(credit_signer_seeds);
+                    token::transfer(cpi_ctx, amount)?;
+
+                    emit!(JobWithdrew {
+                        job: job.key(),
+                        to: user_credit_token_account.owner,
+                        token: credit_mint.key(),
+                        amount,
+                    });
+                    emit!(JobCreditRefunded {
+                        job: job.key(),
+                        to: user_credit_token_account.owner,
+                        amount,
+                    });
+                }
+                CreditRefundPolicy::Issuer => {
+                    let issuer_credit_token_account = issuer_credit_token_account
+                        .ok_or(ErrorCodes::InvalidCreditIssuer)?;
+
+                    let cpi_accounts = Transfer {
+                        from: program_credit_token_account.to_account_info(),
+                        to: issuer_credit_token_account.to_account_info(),
+                        authority: program_credit_token_account.to_account_info(),
+                    };
+                    let cpi_ctx = CpiContext::new(
+                        token_program.to_account_info(),
+                        cpi_accounts,
+                    )
+                    .with_signer(credit_signer_seeds);
+                    token::transfer(cpi_ctx, amount)?;
+
+                    emit!(JobCreditReturnedToIssuer {
+                        job: job.key(),
+                        issuer: issuer_credit_token_account.owner,
+                        amount,
+                    });
+                }
+                CreditRefundPolicy::Burn => {
+                    // Burned through the credits program so its burn events stay complete
+                    let cpi_ctx = CpiContext::new(
+                        credit_program.to_account_info(),
+                        BurnMarketCredits {
+                            state: state.to_account_info(),
+                            credit_mint: credit_mint.to_account_info(),
+                            market_program_credit_token_account: program_credit_token_account.to_account_info(),
+                            token_program: token_program.to_account_info(),
+                        },
+                    )
+                    .with_signer(credit_signer_seeds);
+                    oyster_credits::cpi::burn_market_credits(cpi_ctx, amount, job.key())?;
+
+                    emit!(JobCreditBurned {
+                        job: job.key(),
+                        amount,
+                    });
+                }
             }
 
             Ok(())
@@ -960,10 +3187,163 @@ pub mod market_v {
 pub struct Provider {
     #[max_len(100)]
     pub cp: String,
-    pub owner: Pubkey
+    pub owner: Pubkey,          // Current authority
+    pub id: Pubkey,             // Registering key, seeds the account and is referenced by jobs
+    pub pending_owner: Pubkey,  // Proposed authority during key rotation
+    pub status: ProviderStatus,
+    pub active_jobs: u64,       // Jobs opened and not yet closed
+    #[max_len(MAX_BENEFICIARIES)]
+    pub beneficiaries: Vec<Beneficiary>, // Payout split, empty to pay the owner
+    pub has_rate_card: bool,    // Jobs must meet the published rate card
+    pub bond: u64,              // Bonded amount of the bond mint
+    pub bond_mint: Pubkey,      // Market token the bond was posted in, keys its bond vault
+    pub unbonding_amount: u64,  // Amount waiting out the unbonding period, still slashable
+    pub unbond_at: i64,         // When the unbonding amount can be withdrawn
+    pub suspended: bool,        // Suspended by the admin, takes no new jobs
+}
+
+// Request to register as a provider in a permissioned market
+#[account]
+#[derive(InitSpace)]
+pub struct ProviderApplication {
+    pub applicant: Pubkey,      // Key that will register the provider
+    #[max_len(100)]
+    pub cp: String,             // Control plane URL for the admin to review
+    pub approved: bool,
+}
+
+// Minimum rates a provider charges, with a pending change waiting out the notice period
+#[account]
+#[derive(InitSpace)]
+pub struct RateCard {
+    pub provider: Pubkey,       // Provider account
+    #[max_len(MAX_RATE_CARD_ENTRIES)]
+    pub entries: Vec<RateCardEntry>,
+    #[max_len(MAX_RATE_CARD_ENTRIES)]
+    pub pending_entries: Vec<RateCardEntry>,
+    pub effective_at: i64,      // When pending entries replace the current ones, 0 if none
+}
+
+impl RateCard {
+    // Entries in force at `now`
+    pub fn active_entries(&self, now: i64) -> &Vec<RateCardEntry> {
+        if self.effective_at != 0 && now >= self.effective_at {
+            &self.pending_entries
+        } else {
+            &self.entries
+        }
+    }
+
+    pub fn apply_pending(&mut self, now: i64) {
+        if self.effective_at != 0 && now >= self.effective_at {
+            self.entries = std::mem::take(&mut self.pending_entries);
+            self.effective_at = 0;
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct RateCardEntry {
+    #[max_len(MAX_RATE_KEY_LEN)]
+    pub instance_type: String,
+    #[max_len(MAX_RATE_KEY_LEN)]
+    pub region: String,         // Empty to match any region
+    pub min_rate: u64,
+}
+
+// Extended provider details, reallocated as fields grow or shrink
+#[account]
+pub struct ProviderProfile {
+    pub provider: Pubkey,            // Provider account
+    pub version: u64,                // Incremented on every update
+    pub cps: Vec<String>,            // Control plane endpoints
+    pub regions: Vec<String>,        // Region codes
+    pub instance_types: Vec<String>, // Supported instance types
+    pub attestation_key: Pubkey,     // Enclave attestation key
+    pub metadata_uri: String,        // Contact / metadata URI
+}
+
+impl ProviderProfile {
+    // Account size (including discriminator) holding the given fields
+    pub fn space(cps: &[String], regions: &[String], instance_types: &[String], metadata_uri: &str) -> usize {
+        let list = |items: &[String]| 4 + items.iter().map(|item| 4 + item.len()).sum::<usize>();
+        8 + 32 + 8 + list(cps) + list(regions) + list(instance_types) + 32 + 4 + metadata_uri.len()
+    }
+
+    // Control plane endpoints with `cp` as the primary one
+    pub fn cps_with_primary(&self, cp: &str) -> Vec<String> {
+        let mut cps = self.cps.clone();
+        match cps.first_mut() {
+            Some(primary) => *primary = cp.to_string(),
+            None => cps.push(cp.to_string()),
+        }
+        cps
+    }
+
+    // Account size after applying `update`
+    pub fn space_after(&self, update: &ProfileUpdate) -> usize {
+        Self::space(
+            update.cps.as_ref().unwrap_or(&self.cps),
+            update.regions.as_ref().unwrap_or(&self.regions),
+            update.instance_types.as_ref().unwrap_or(&self.instance_types),
+            update.metadata_uri.as_ref().unwrap_or(&self.metadata_uri),
+        )
+    }
+}
+
+// Profile fields to change, `None` leaves a field as is
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProfileUpdate {
+    pub cps: Option<Vec<String>>,
+    pub regions: Option<Vec<String>>,
+    pub instance_types: Option<Vec<String>>,
+    pub attestation_key: Option<Pubkey>,
+    pub metadata_uri: Option<String>,
+}
+
+impl ProfileUpdate {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(cps) = &self.cps {
+            require!(!cps.is_empty(), ErrorCodes::InvalidControlPlaneUrl);
+        }
+        for list in [&self.cps, &self.regions, &self.instance_types].into_iter().flatten() {
+            require!(list.len() <= MAX_PROFILE_ENTRIES, ErrorCodes::InvalidProfile);
+            require!(
+                list.iter().all(|item| !item.is_empty() && item.len() <= MAX_PROFILE_ENTRY_LEN),
+                ErrorCodes::InvalidProfile
+            );
+        }
+        if let Some(metadata_uri) = &self.metadata_uri {
+            require!(metadata_uri.len() <= MAX_METADATA_URI_LEN, ErrorCodes::InvalidProfile);
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct Beneficiary {
+    pub wallet: Pubkey,
+    pub share_bps: u16,
+}
+
+// Key allowed to act for a provider within its permissions
+#[account]
+#[derive(InitSpace)]
+pub struct ProviderOperator {
+    pub provider: Pubkey,       // Provider account
+    pub operator: Pubkey,       // Operator key
+    pub permissions: u8,        // OPERATOR_* flags
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ProviderStatus {
+    Active,
+    WindingDown, // Removed, waiting for active jobs to close
 }
 
-// Market state
+// Market state. `Market`, `Provider` and `Job` have grown since the first deployment and
+// accounts created by it are not migrated, so upgrading needs a fresh deployment rather than an
+// in-place program upgrade.
 #[account]
 pub struct Market {
     pub admin: Pubkey,          // Admin authority
@@ -971,7 +3351,24 @@ pub struct Market {
     pub token_mint: Pubkey,     // Token mint address
     pub credit_mint: Pubkey,    // Credit mint address
     pub job_index: u128,        // Job index counter
-    pub notice_period: u64
+    pub notice_period: u64,
+    pub credit_refund_policy: CreditRefundPolicy, // Destination of unused credits
+    pub credit_issuer: Pubkey,  // Credit issuer, used by the issuer refund policy
+    pub min_provider_bond: u64, // Bond a provider must hold to take new jobs
+    pub unbonding_period: u64,  // Delay before an unbonded amount can be withdrawn
+    pub permissioned: bool,     // Providers need an admin-approved application to register
+    pub accept_timeout: u64,    // Time a provider has to accept a job, 0 for no timeout
+    pub tombstone_jobs: bool,   // Keep a small record of closed jobs instead of deleting them
+    pub reap_bounty: u64,       // Lamports paid out of the job rent to whoever reaps an exhausted job
+    pub min_sponsor_deposit: u64, // Smallest sponsorship, keeps sponsor slots from being spammed
+}
+
+// Destination of unused credits on job withdraw and close
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CreditRefundPolicy {
+    User,   // Back to the job owner
+    Issuer, // Back to the credit issuer
+    Burn,   // Burned
 }
 
 // Job account
@@ -987,6 +3384,85 @@ pub struct Job {
     pub balance: u64,           // Job balance
     pub last_settled: u64,      // Last settled timestamp
     pub credit_balance: u64,    // Credit balance
+    #[max_len(100)]
+    pub provider_cp: String,    // Provider control plane URL when the job was opened
+    pub end_at: u64,            // End of billing after a close request or provider termination, 0 if none
+    pub accept_deadline: u64,   // Pending jobs can be expired after this, 0 for no expiry
+    pub status: JobStatus,
+    pub runway_threshold: u64,  // Runway in seconds below which owners are warned
+    pub low_balance_level: LowBalanceLevel, // Last reported low balance warning
+    pub sponsor_refund_policy: SponsorRefundPolicy,
+    pub sponsor_seq: u64,       // Seeds the next sponsorship
+    pub sponsors_outstanding: u64, // Sponsorships not yet refunded
+    pub sponsor_claims: u64,    // Sum of outstanding sponsorship deposits
+    pub sponsored_balance: u64, // Part of the token balance backing sponsorships
+    pub pending_owner: Pubkey,  // Proposed owner during an ownership transfer
+    pub nft_mint: Pubkey,       // Mint of the job NFT for tokenized jobs, default otherwise
+}
+
+// How unused sponsor funds are shared when sponsors are refunded
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum SponsorRefundPolicy {
+    ProRata, // In proportion to each sponsor's deposit
+    Lifo,    // Latest sponsor first, in full while funds last
+}
+
+// Tokens deposited into a job by a third party, refundable to them
+#[account]
+#[derive(InitSpace)]
+pub struct JobSponsorship {
+    pub job: Pubkey,            // Job account
+    pub sponsor: Pubkey,        // Depositor, receives refunds
+    pub seq: u64,               // Position among the job's sponsorships
+    pub amount: u64,            // Amount deposited
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, InitSpace)]
+pub enum LowBalanceLevel {
+    None,
+    BelowThreshold, // Runway below the owner's threshold
+    BelowNotice,    // Runway below the market notice period
+}
+
+impl Job {
+    // Size of a closed job kept as a tombstone, with its metadata and control plane cleared
+    pub const TOMBSTONE_SPACE: usize = 8 + Job::INIT_SPACE - 1500 - 100;
+}
+
+// Automatic top-up settings of a job
+#[account]
+#[derive(InitSpace)]
+pub struct JobTopupPolicy {
+    pub job: Pubkey,            // Job account
+    pub owner: Pubkey,          // Job owner that set up the policy, top-ups stop if the job changes hands
+    pub target_runway: u64,     // Runway in seconds to top up to
+    pub max_per_topup: u64,     // Largest single top-up
+    pub monthly_cap: u64,       // Total top-ups allowed per period
+    pub period_start: u64,      // Start of the current cap period
+    pub period_topped_up: u64,  // Topped up in the current period
+}
+
+// Returned by `job_settlement_preview`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct JobSettlementPreview {
+    pub amount_due: u64,         // Paid to the provider if settled now
+    pub credit_amount: u64,      // Part of the amount due paid in credits
+    pub token_amount: u64,       // Part of the amount due paid in tokens
+    pub refundable_balance: u64, // Withdrawable by the owner after settling
+    pub runway: u64,             // Seconds of billing the remaining balance pays for
+}
+
+// Job lifecycle:
+//   Pending -> Active (accepted) | Closed (rejected, expired or closed by the owner)
+//   Active -> Closing (close requested) | Terminated (provider termination) | Closed
+//   Closing, Terminated -> Closed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum JobStatus {
+    Pending,    // Waiting for the provider to accept, not billed
+    Active,     // Billed at the job rate
+    Closing,    // Close requested by the owner, billed until the notice ends
+    Terminated, // Terminated by the provider, billed until the notice ends
+    Closed,     // Closed, only kept as a tombstone
 }
 
 // Contexts
@@ -1021,172 +3497,1398 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
-// Context for adding a provider
-#[derive(Accounts)]
-pub struct ProviderAdd<'info> {
-    // PDA for the provider account
+// Context for adding a provider
+#[derive(Accounts)]
+pub struct ProviderAdd<'info> {
+    #[account(
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    // PDA for the provider account
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Provider::INIT_SPACE,
+        seeds = [b"provider", authority.key().as_ref()],
+        bump
+    )]
+    pub provider: Account<'info, Provider>,
+
+    // Provider application, required in a permissioned market and closed on registration
+    #[account(
+        mut,
+        seeds = [b"provider_application", authority.key().as_ref()],
+        bump
+    )]
+    pub application: Option<Account<'info, ProviderApplication>>,
+
+    #[account(
+        constraint = token_mint.key() == market.token_mint @ ErrorCodes::InvalidMint
+    )]
+    pub token_mint: Account<'info, Mint>,
+
+    // Bond vault shared by all providers of the market token
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [b"provider_bond", token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = bond_token_account
+    )]
+    pub bond_token_account: Account<'info, TokenAccount>,
+
+    // Required when posting a bond
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = authority
+    )]
+    pub authority_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Authority (signer)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    // System program
+    pub system_program: Program<'info, System>,
+}
+
+// Context for applying to register as a provider
+#[derive(Accounts)]
+pub struct ProviderApply<'info> {
+    #[account(
+        init,
+        payer = applicant,
+        space = 8 + ProviderApplication::INIT_SPACE,
+        seeds = [b"provider_application", applicant.key().as_ref()],
+        bump
+    )]
+    pub application: Account<'info, ProviderApplication>,
+
+    #[account(mut)]
+    pub applicant: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Context for approving a provider application
+#[derive(Accounts)]
+pub struct ProviderApplicationApprove<'info> {
+    #[account(
+        seeds = [b"market"],
+        bump,
+        has_one = admin @ ErrorCodes::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_application", application.applicant.as_ref()],
+        bump
+    )]
+    pub application: Account<'info, ProviderApplication>,
+}
+
+// Context for rejecting a provider application
+#[derive(Accounts)]
+pub struct ProviderApplicationReject<'info> {
+    #[account(
+        seeds = [b"market"],
+        bump,
+        has_one = admin @ ErrorCodes::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        close = applicant,
+        seeds = [b"provider_application", application.applicant.as_ref()],
+        bump
+    )]
+    pub application: Account<'info, ProviderApplication>,
+
+    /// CHECK: receives the application rent
+    #[account(mut, address = application.applicant)]
+    pub applicant: UncheckedAccount<'info>,
+}
+
+// Context for admin actions on a provider
+#[derive(Accounts)]
+pub struct ProviderAdminAction<'info> {
+    #[account(
+        seeds = [b"market"],
+        bump,
+        has_one = admin @ ErrorCodes::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"provider", provider.id.as_ref()],
+        bump
+    )]
+    pub provider: Account<'info, Provider>,
+}
+
+// Context for removing a provider
+#[derive(Accounts)]
+pub struct ProviderRemove<'info> {
+    // PDA for the provider account
+    #[account(
+        mut,
+        seeds = [b"provider", provider.id.as_ref()],
+        bump,
+        constraint = provider.owner == authority.key() @ ErrorCodes::Unauthorized
+    )]
+    pub provider: Account<'info, Provider>,
+
+    // Authority (signer)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+// Context for closing a removed provider
+#[derive(Accounts)]
+pub struct ProviderClose<'info> {
+    // PDA for the provider account
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"provider", provider.id.as_ref()],
+        bump,
+        constraint = provider.owner == authority.key() @ ErrorCodes::Unauthorized
+    )]
+    pub provider: Account<'info, Provider>,
+
+    /// CHECK: provider profile, closed along with the provider if it was created
+    #[account(
+        mut,
+        seeds = [b"provider_profile", provider.key().as_ref()],
+        bump
+    )]
+    pub profile: UncheckedAccount<'info>,
+
+    /// CHECK: provider rate card, closed along with the provider if it was created
+    #[account(
+        mut,
+        seeds = [b"rate_card", provider.key().as_ref()],
+        bump
+    )]
+    pub rate_card: UncheckedAccount<'info>,
+
+    // Authority (signer)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+// Context for updating a provider's control plane URL
+#[derive(Accounts)]
+pub struct ProviderUpdateWithCp<'info> {
+    // PDA for the provider account
+    #[account(mut)]
+    pub provider: Account<'info, Provider>,
+
+    /// CHECK: provider profile, updated along with the provider if it was created
+    #[account(
+        mut,
+        seeds = [b"provider_profile", provider.key().as_ref()],
+        bump
+    )]
+    pub profile: UncheckedAccount<'info>,
+
+    // Provider or operator (signer), pays for any growth of the profile
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // Required when the authority is an operator
+    #[account(
+        seeds = [b"provider_operator", provider.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub operator: Option<Box<Account<'info, ProviderOperator>>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Context for creating a provider profile
+#[derive(Accounts)]
+pub struct ProviderProfileInit<'info> {
+    // PDA for the provider account
+    #[account(
+        seeds = [b"provider", provider.id.as_ref()],
+        bump,
+        constraint = provider.owner == authority.key() @ ErrorCodes::Unauthorized
+    )]
+    pub provider: Account<'info, Provider>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = ProviderProfile::space(std::slice::from_ref(&provider.cp), &[], &[], ""),
+        seeds = [b"provider_profile", provider.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, ProviderProfile>,
+
+    // Authority (signer)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Context for updating a provider profile
+#[derive(Accounts)]
+#[instruction(update: ProfileUpdate)]
+pub struct ProviderProfileSet<'info> {
+    // PDA for the provider account. The authority is checked here so that unauthorized callers
+    // cannot resize the profile.
+    #[account(
+        mut,
+        seeds = [b"provider", provider.id.as_ref()],
+        bump,
+        constraint = market_v::utils_mod::require_provider_permission(
+            &provider,
+            operator.as_deref(),
+            authority.key,
+            OPERATOR_UPDATE_CP,
+        ).is_ok() @ ErrorCodes::Unauthorized
+    )]
+    pub provider: Account<'info, Provider>,
+
+    #[account(
+        mut,
+        realloc = profile.space_after(&update),
+        realloc::payer = authority,
+        realloc::zero = false,
+        seeds = [b"provider_profile", provider.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, ProviderProfile>,
+
+    // Provider or operator (signer), pays for any growth of the profile
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // Required when the authority is an operator
+    #[account(
+        seeds = [b"provider_operator", provider.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub operator: Option<Box<Account<'info, ProviderOperator>>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Context for publishing a provider rate card
+#[derive(Accounts)]
+pub struct ProviderRateCardSet<'info> {
+    #[account(
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    // PDA for the provider account
+    #[account(
+        mut,
+        seeds = [b"provider", provider.id.as_ref()],
+        bump,
+        constraint = provider.owner == authority.key() @ ErrorCodes::Unauthorized
+    )]
+    pub provider: Account<'info, Provider>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + RateCard::INIT_SPACE,
+        seeds = [b"rate_card", provider.key().as_ref()],
+        bump
+    )]
+    pub rate_card: Account<'info, RateCard>,
+
+    // Authority (signer)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Context for updating a provider's payout configuration
+#[derive(Accounts)]
+pub struct ProviderSetPayout<'info> {
+    // PDA for the provider account
+    #[account(
+        mut,
+        seeds = [b"provider", provider.id.as_ref()],
+        bump,
+        constraint = provider.owner == authority.key() @ ErrorCodes::Unauthorized
+    )]
+    pub provider: Account<'info, Provider>,
+
+    // Authority (signer)
+    pub authority: Signer<'info>,
+}
+
+// Context for adding to a provider bond
+#[derive(Accounts)]
+pub struct ProviderBondDeposit<'info> {
+    #[account(
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    // PDA for the provider account
+    #[account(
+        mut,
+        seeds = [b"provider", provider.id.as_ref()],
+        bump,
+        constraint = provider.owner == authority.key() @ ErrorCodes::Unauthorized
+    )]
+    pub provider: Account<'info, Provider>,
+
+    #[account(
+        constraint = token_mint.key() == market.token_mint
+    )]
+    pub token_mint: Account<'info, Mint>,
+
+    // Bond vault shared by all providers of the market token
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [b"provider_bond", token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = bond_token_account
+    )]
+    pub bond_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = authority
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    // Authority (signer)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// Context for starting to unbond a provider bond
+#[derive(Accounts)]
+pub struct ProviderUnbondRequest<'info> {
+    #[account(
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    // PDA for the provider account
+    #[account(
+        mut,
+        seeds = [b"provider", provider.id.as_ref()],
+        bump,
+        constraint = provider.owner == authority.key() @ ErrorCodes::Unauthorized
+    )]
+    pub provider: Account<'info, Provider>,
+
+    // Authority (signer)
+    pub authority: Signer<'info>,
+}
+
+// Context for withdrawing an unbonded amount
+#[derive(Accounts)]
+pub struct ProviderUnbondWithdraw<'info> {
+    #[account(
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    // PDA for the provider account
+    #[account(
+        mut,
+        seeds = [b"provider", provider.id.as_ref()],
+        bump,
+        constraint = provider.owner == authority.key() @ ErrorCodes::Unauthorized
+    )]
+    pub provider: Account<'info, Provider>,
+
+    // Mint the provider bonded in, which may predate a market token update
+    #[account(
+        constraint = token_mint.key() == provider.bond_mint @ ErrorCodes::InvalidMint
+    )]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = bond_token_account
+    )]
+    pub bond_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = authority
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+
+    // Authority (signer)
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+// Context for slashing a provider bond in favour of a job owner
+#[derive(Accounts)]
+#[instruction(job_index: u128)]
+pub struct ProviderSlash<'info> {
+    #[account(
+        seeds = [b"market"],
+        bump,
+        has_one = admin @ ErrorCodes::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"job", job_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub job: Account<'info, Job>,
+
+    #[account(
+        mut,
+        seeds = [b"provider", job.provider.as_ref()],
+        bump
+    )]
+    pub provider_account: Account<'info, Provider>,
+
+    // Mint the provider bonded in, which may predate a market token update
+    #[account(
+        constraint = token_mint.key() == provider_account.bond_mint @ ErrorCodes::InvalidMint
+    )]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"provider_bond", token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = bond_token_account
+    )]
+    pub bond_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = token_mint
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    // Holder's job NFT account, required for tokenized jobs
+    pub job_nft_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+// Context for proposing a provider key rotation
+#[derive(Accounts)]
+pub struct ProviderRotateKey<'info> {
+    // PDA for the provider account
+    #[account(
+        mut,
+        seeds = [b"provider", provider.id.as_ref()],
+        bump,
+        constraint = provider.owner == authority.key() @ ErrorCodes::Unauthorized
+    )]
+    pub provider: Account<'info, Provider>,
+
+    // Current authority (signer)
+    pub authority: Signer<'info>,
+}
+
+// Context for accepting a provider key rotation
+#[derive(Accounts)]
+pub struct ProviderAcceptKey<'info> {
+    // PDA for the provider account
+    #[account(
+        mut,
+        seeds = [b"provider", provider.id.as_ref()],
+        bump,
+        constraint = provider.pending_owner == new_owner.key() @ ErrorCodes::Unauthorized
+    )]
+    pub provider: Account<'info, Provider>,
+
+    // Proposed authority (signer)
+    pub new_owner: Signer<'info>,
+}
+
+// Context for registering a provider operator
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct ProviderOperatorSet<'info> {
+    // PDA for the provider account
+    #[account(
+        seeds = [b"provider", provider.id.as_ref()],
+        bump,
+        constraint = provider.owner == authority.key() @ ErrorCodes::Unauthorized
+    )]
+    pub provider: Account<'info, Provider>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ProviderOperator::INIT_SPACE,
+        seeds = [b"provider_operator", provider.key().as_ref(), operator.as_ref()],
+        bump
+    )]
+    pub provider_operator: Account<'info, ProviderOperator>,
+
+    // Authority (signer)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Context for removing a provider operator
+#[derive(Accounts)]
+pub struct ProviderOperatorRemove<'info> {
+    // PDA for the provider account
+    #[account(
+        seeds = [b"provider", provider.id.as_ref()],
+        bump,
+        constraint = provider.owner == authority.key() @ ErrorCodes::Unauthorized
+    )]
+    pub provider: Account<'info, Provider>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"provider_operator", provider.key().as_ref(), provider_operator.operator.as_ref()],
+        bump
+    )]
+    pub provider_operator: Account<'info, ProviderOperator>,
+
+    // Authority (signer)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+// Context for updating the token mint address
+#[derive(Accounts)]
+pub struct UpdateToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"market"],
+        bump,
+        has_one = admin @ ErrorCodes::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+// Context for admin updates to the market configuration
+#[derive(Accounts)]
+pub struct UpdateMarket<'info> {
+    #[account(
+        mut,
+        seeds = [b"market"],
+        bump,
+        has_one = admin @ ErrorCodes::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    pub admin: Signer<'info>,
+}
+
+// Context for opening a job
+#[derive(Accounts)]
+#[instruction(metadata: String, provider: Pubkey)]
+pub struct JobOpen<'info> {
+    #[account(
+        mut,
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Job::INIT_SPACE,
+        seeds = [b"job", market.job_index.to_le_bytes().as_ref()], // Use job_index as seed
+        bump
+    )]
+    pub job: Box<Account<'info, Job>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // #[account(mut)]
+    // pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = token_mint.key() == market.token_mint
+    )]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"job_token", token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = program_token_account
+    )]
+    pub program_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    // Jobs can only be opened against a registered provider
+    #[account(
+        mut,
+        seeds = [b"provider", provider.as_ref()],
+        bump,
+        constraint = !provider_account.cp.is_empty() @ ErrorCodes::ProviderNotFound
+    )]
+    pub provider_account: Box<Account<'info, Provider>>,
+
+    // Provider's rate card, required when the provider publishes one
+    #[account(
+        seeds = [b"rate_card", provider_account.key().as_ref()],
+        bump
+    )]
+    pub rate_card: Option<Box<Account<'info, RateCard>>>,
+
+    #[account(
+        mut,
+        // constraint = credit_mint.key() == market.credit_mint
+    )]
+    pub credit_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [b"credit_token", credit_mint.key().as_ref()],
+        bump,
+        token::mint = credit_mint,
+        token::authority = program_credit_token_account
+        // mut,
+        // constraint = program_credit_token_account.owner == system_program.key()
+    )]
+    pub program_credit_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = user_credit_token_account.owner == owner.key()
+    )]
+    pub user_credit_token_account: Box<Account<'info, TokenAccount>>,
+
+    // Mint of the job NFT, only for tokenized jobs
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"job_nft", job.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = job,
+        mint::freeze_authority = job
+    )]
+    pub job_nft_mint: Option<Box<Account<'info, Mint>>>,
+
+    // Owner's account receiving the job NFT
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = job_nft_mint,
+        associated_token::authority = owner
+    )]
+    pub owner_nft_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub system_program: Program<'info, System>,
+}
+
+// Context for settling a job
+#[derive(Accounts)]
+#[instruction(job_index: u128)]
+pub struct JobSettle<'info> {
+    #[account(
+        mut,
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"job", job_index.to_le_bytes().as_ref()], // Use job_index as seed
+        bump,
+        constraint = job.index == job_index
+    )]
+    pub job: Account<'info, Job>,
+
+    #[account(mut)]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(mut, seeds = [b"job_token", token_mint.key().as_ref()], bump)]
+    pub program_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"provider", job.provider.as_ref()],
+        bump
+    )]
+    pub provider_account: Box<Account<'info, Provider>>,
+
+    #[account(
+        mut,
+        constraint = provider_token_account.owner == provider_account.owner,
+    )]
+    pub provider_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        // constraint = credit_mint.key() == market.credit_mint
+    )]
+    pub credit_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [b"credit_token", credit_mint.key().as_ref()],
+        bump,
+        token::mint = credit_mint,
+        token::authority = program_credit_token_account
+        // mut,
+        // constraint = program_credit_token_account.owner == system_program.key()
+    )]
+    pub program_credit_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: account is verified in credit program
+    #[account(mut)]
+    pub state: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(mut)]
+    pub credit_program_usdc_token_account: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(mut)]
+    pub credit_program_reserve: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(
+        seeds = [b"provider_tier", job.provider.as_ref()],
+        bump,
+        seeds::program = credit_program.key()
+    )]
+    pub credit_program_provider_tier: UncheckedAccount<'info>,
+
+    pub credit_program: Program<'info, OysterCredits>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// Context for requesting a job close
+#[derive(Accounts)]
+#[instruction(job_index: u128)]
+pub struct JobCloseRequest<'info> {
+    #[account(
+        mut,
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"job", job_index.to_le_bytes().as_ref()], // Use job_index as seed
+        bump,
+        constraint = job.index == job_index
+    )]
+    pub job: Account<'info, Job>,
+
+    #[account(mut)]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(mut, seeds = [b"job_token", token_mint.key().as_ref()], bump)]
+    pub program_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"provider", job.provider.as_ref()],
+        bump
+    )]
+    pub provider_account: Box<Account<'info, Provider>>,
+
+    #[account(
+        mut,
+        constraint = provider_token_account.owner == provider_account.owner,
+    )]
+    pub provider_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        // constraint = credit_mint.key() == market.credit_mint
+    )]
+    pub credit_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [b"credit_token", credit_mint.key().as_ref()],
+        bump,
+        token::mint = credit_mint,
+        token::authority = program_credit_token_account
+        // mut,
+        // constraint = program_credit_token_account.owner == system_program.key()
+    )]
+    pub program_credit_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // Holder's job NFT account, required for tokenized jobs
+    pub job_nft_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// CHECK: account is verified in credit program
+    #[account(mut)]
+    pub state: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(mut)]
+    pub credit_program_usdc_token_account: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(mut)]
+    pub credit_program_reserve: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(
+        seeds = [b"provider_tier", job.provider.as_ref()],
+        bump,
+        seeds::program = credit_program.key()
+    )]
+    pub credit_program_provider_tier: UncheckedAccount<'info>,
+
+    pub credit_program: Program<'info, OysterCredits>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// Context for closing a job
+#[derive(Accounts)]
+#[instruction(job_index: u128)]
+pub struct JobClose<'info> { 
+    #[account(
+        mut,
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        seeds = [b"job", job_index.to_le_bytes().as_ref()], // Use job_index as seed
+        bump,
+        constraint = job.index == job_index
+    )] // Closed or tombstoned by the instruction, rent refunded to the owner
+    pub job: Box<Account<'info, Job>>,
+
+    /// CHECK: job top-up policy, closed along with the job if it was set up
+    #[account(
+        mut,
+        seeds = [b"topup_policy", job.key().as_ref()],
+        bump
+    )]
+    pub topup_policy: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut, seeds = [b"job_token", token_mint.key().as_ref()], bump)]
+    pub program_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.owner == owner.key(),
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"provider", job.provider.as_ref()],
+        bump
+    )]
+    pub provider_account: Box<Account<'info, Provider>>,
+
+    // Job NFT, burned when a tokenized job is closed
+    #[account(mut, address = job.nft_mint @ ErrorCodes::InvalidMint)]
+    pub job_nft_mint: Option<Box<Account<'info, Mint>>>,
+
+    // Holder's job NFT account, required for tokenized jobs
+    #[account(mut)]
+    pub job_nft_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = provider_token_account.owner == provider_account.owner
+    )]
+    pub provider_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        // constraint = credit_mint.key() == market.credit_mint
+    )]
+    pub credit_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [b"credit_token", credit_mint.key().as_ref()],
+        bump,
+        token::mint = credit_mint,
+        token::authority = program_credit_token_account
+        // mut,
+        // constraint = program_credit_token_account.owner == system_program.key()
+    )]
+    pub program_credit_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_credit_token_account.owner == owner.key()
+    )]
+    pub user_credit_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = issuer_credit_token_account.owner == market.credit_issuer @ ErrorCodes::InvalidCreditIssuer,
+        constraint = issuer_credit_token_account.mint == credit_mint.key() @ ErrorCodes::InvalidMint
+    )]
+    pub issuer_credit_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>, // Owner must sign the transaction
+
+    /// CHECK: account is verified in credit program
+    #[account(mut)]
+    pub state: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(mut)]
+    pub credit_program_usdc_token_account: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(mut)]
+    pub credit_program_reserve: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(
+        seeds = [b"provider_tier", job.provider.as_ref()],
+        bump,
+        seeds::program = credit_program.key()
+    )]
+    pub credit_program_provider_tier: UncheckedAccount<'info>,
+
+    pub credit_program: Program<'info, OysterCredits>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// Context for a provider accepting a pending job
+#[derive(Accounts)]
+#[instruction(job_index: u128)]
+pub struct JobAccept<'info> {
+    #[account(
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        seeds = [b"job", job_index.to_le_bytes().as_ref()], // Use job_index as seed
+        bump,
+        constraint = job.index == job_index
+    )]
+    pub job: Box<Account<'info, Job>>,
+
+    #[account(
+        seeds = [b"provider", job.provider.as_ref()],
+        bump
+    )]
+    pub provider_account: Box<Account<'info, Provider>>,
+
+    // Required when an operator accepts
+    #[account(
+        seeds = [b"provider_operator", provider_account.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub operator: Option<Box<Account<'info, ProviderOperator>>>,
+
+    // Provider or operator
+    pub authority: Signer<'info>,
+}
+
+// Context for rejecting or expiring a pending job
+#[derive(Accounts)]
+#[instruction(job_index: u128)]
+pub struct JobCancelPending<'info> {
+    #[account(
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        seeds = [b"job", job_index.to_le_bytes().as_ref()], // Use job_index as seed
+        bump,
+        constraint = job.index == job_index
+    )] // Closed or tombstoned by the instruction, rent refunded to the owner
+    pub job: Box<Account<'info, Job>>,
+
+    /// CHECK: job top-up policy, closed along with the job if it was set up
+    #[account(
+        mut,
+        seeds = [b"topup_policy", job.key().as_ref()],
+        bump
+    )]
+    pub topup_policy: UncheckedAccount<'info>,
+
+    /// CHECK: receives the job rent, must be the job owner
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+
+    // Job NFT, retired when a tokenized job is closed
+    #[account(mut, address = job.nft_mint @ ErrorCodes::InvalidMint)]
+    pub job_nft_mint: Option<Box<Account<'info, Mint>>>,
+
+    // Holder's job NFT account, required for tokenized jobs
+    #[account(mut)]
+    pub job_nft_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        constraint = token_mint.key() == market.token_mint @ ErrorCodes::InvalidMint
+    )]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut, seeds = [b"job_token", token_mint.key().as_ref()], bump)]
+    pub program_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"provider", job.provider.as_ref()],
+        bump
+    )]
+    pub provider_account: Box<Account<'info, Provider>>,
+
+    // Required when an operator rejects
+    #[account(
+        seeds = [b"provider_operator", provider_account.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub operator: Option<Box<Account<'info, ProviderOperator>>>,
+
+    #[account(
+        mut,
+        constraint = credit_mint.key() == market.credit_mint @ ErrorCodes::InvalidMint
+    )]
+    pub credit_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"credit_token", credit_mint.key().as_ref()],
+        bump,
+        token::mint = credit_mint
+    )]
+    pub program_credit_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = credit_mint)]
+    pub user_credit_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = issuer_credit_token_account.owner == market.credit_issuer @ ErrorCodes::InvalidCreditIssuer,
+        constraint = issuer_credit_token_account.mint == credit_mint.key() @ ErrorCodes::InvalidMint
+    )]
+    pub issuer_credit_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Provider or operator when rejecting, anyone when expiring
+    pub authority: Signer<'info>,
+
+    /// CHECK: account is verified in credit program
+    #[account(mut)]
+    pub state: UncheckedAccount<'info>,
+
+    pub credit_program: Program<'info, OysterCredits>,
+    pub token_program: Program<'info, Token>,
+}
+
+// Context for a provider terminating a job
+#[derive(Accounts)]
+#[instruction(job_index: u128)]
+pub struct JobTerminateByProvider<'info> {
+    #[account(
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        seeds = [b"job", job_index.to_le_bytes().as_ref()], // Use job_index as seed
+        bump,
+        constraint = job.index == job_index
+    )]
+    pub job: Box<Account<'info, Job>>,
+
+    #[account(
+        seeds = [b"provider", job.provider.as_ref()],
+        bump
+    )]
+    pub provider_account: Box<Account<'info, Provider>>,
+
+    pub authority: Signer<'info>,
+}
+
+// Context for setting a job top-up policy
+#[derive(Accounts)]
+#[instruction(job_index: u128)]
+pub struct JobTopupPolicySet<'info> {
+    #[account(
+        mut,
+        seeds = [b"job", job_index.to_le_bytes().as_ref()], // Use job_index as seed
+        bump,
+        constraint = job.index == job_index
+    )]
+    pub job: Box<Account<'info, Job>>,
+
     #[account(
-        init,
-        payer = authority,
-        space = 8 + Provider::INIT_SPACE,
-        seeds = [b"provider", authority.key().as_ref()],
+        init_if_needed,
+        payer = owner,
+        space = 8 + JobTopupPolicy::INIT_SPACE,
+        seeds = [b"topup_policy", job.key().as_ref()],
         bump
     )]
-    pub provider: Account<'info, Provider>,
+    pub policy: Account<'info, JobTopupPolicy>,
 
-    // Authority (signer)
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub owner: Signer<'info>,
+
+    // Holder's job NFT account, required for tokenized jobs
+    pub job_nft_account: Option<Box<Account<'info, TokenAccount>>>,
 
-    // System program
     pub system_program: Program<'info, System>,
 }
 
-// Context for removing a provider
+// Context for removing a job top-up policy
 #[derive(Accounts)]
-pub struct ProviderRemove<'info> {
-    // PDA for the provider account
+#[instruction(job_index: u128)]
+pub struct JobTopupPolicyRemove<'info> {
     #[account(
         mut,
-        close = authority,
-        seeds = [b"provider", authority.key().as_ref()],
+        seeds = [b"job", job_index.to_le_bytes().as_ref()], // Use job_index as seed
+        bump,
+        constraint = job.index == job_index
+    )]
+    pub job: Box<Account<'info, Job>>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"topup_policy", job.key().as_ref()],
         bump
     )]
-    pub provider: Account<'info, Provider>,
+    pub policy: Account<'info, JobTopupPolicy>,
 
-    // Authority (signer)
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub owner: Signer<'info>,
+
+    // Holder's job NFT account, required for tokenized jobs
+    pub job_nft_account: Option<Box<Account<'info, TokenAccount>>>,
 }
 
-// Context for updating a provider's control plane URL
+// Context for an automatic job top-up
 #[derive(Accounts)]
-pub struct ProviderUpdateWithCp<'info> {
-    // PDA for the provider account
+#[instruction(job_index: u128)]
+pub struct JobAutoTopup<'info> {
+    #[account(
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Box<Account<'info, Market>>,
+
     #[account(
         mut,
-        seeds = [b"provider", authority.key().as_ref()],
+        seeds = [b"job", job_index.to_le_bytes().as_ref()], // Use job_index as seed
+        bump,
+        constraint = job.index == job_index
+    )]
+    pub job: Box<Account<'info, Job>>,
+
+    #[account(
+        mut,
+        seeds = [b"topup_policy", job.key().as_ref()],
         bump
     )]
-    pub provider: Account<'info, Provider>,
+    pub policy: Box<Account<'info, JobTopupPolicy>>,
+
+    /// CHECK: delegate approved by owners on their token accounts, signs transfers out of them
+    #[account(seeds = [b"topup_delegate"], bump)]
+    pub topup_delegate: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = token_mint.key() == market.token_mint @ ErrorCodes::InvalidMint
+    )]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut, seeds = [b"job_token", token_mint.key().as_ref()], bump)]
+    pub program_token_account: Box<Account<'info, TokenAccount>>,
 
-    // Authority (signer)
     #[account(mut)]
-    pub authority: Signer<'info>,
-}
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
 
-// Context for updating the token mint address
-#[derive(Accounts)]
-pub struct UpdateToken<'info> {
     #[account(
         mut,
-        seeds = [b"market"],
-        bump,
-        has_one = admin @ ErrorCodes::Unauthorized
+        constraint = credit_mint.key() == market.credit_mint @ ErrorCodes::InvalidMint
     )]
-    pub market: Account<'info, Market>,
+    pub credit_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut, seeds = [b"credit_token", credit_mint.key().as_ref()], bump)]
+    pub program_credit_token_account: Box<Account<'info, TokenAccount>>,
 
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub user_credit_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub keeper: Signer<'info>,
+
+    // Holder's job NFT account, required for tokenized jobs
+    pub job_nft_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub token_program: Program<'info, Token>,
 }
 
-// Context for opening a job
+// Context for owner updates of job settings
 #[derive(Accounts)]
-#[instruction(metadata: String, provider: Pubkey)]
-pub struct JobOpen<'info> {
+#[instruction(job_index: u128)]
+pub struct JobOwnerAction<'info> {
     #[account(
-        mut,
         seeds = [b"market"],
         bump
     )]
     pub market: Box<Account<'info, Market>>,
 
     #[account(
-        init,
-        payer = owner,
-        space = 8 + Job::INIT_SPACE,
-        seeds = [b"job", market.job_index.to_le_bytes().as_ref()], // Use job_index as seed
-        bump
+        mut,
+        seeds = [b"job", job_index.to_le_bytes().as_ref()], // Use job_index as seed
+        bump,
+        constraint = job.index == job_index
     )]
     pub job: Box<Account<'info, Job>>,
 
-    #[account(mut)]
     pub owner: Signer<'info>,
 
-    // #[account(mut)]
-    // pub owner_token_account: Account<'info, TokenAccount>,
+    // Holder's job NFT account, required for tokenized jobs
+    pub job_nft_account: Option<Box<Account<'info, TokenAccount>>>,
+}
 
+// Context for accepting a job ownership transfer
+#[derive(Accounts)]
+#[instruction(job_index: u128)]
+pub struct JobAcceptOwnership<'info> {
     #[account(
         mut,
-        constraint = token_mint.key() == market.token_mint
+        seeds = [b"job", job_index.to_le_bytes().as_ref()], // Use job_index as seed
+        bump,
+        constraint = job.index == job_index,
+        constraint = job.pending_owner == new_owner.key() @ ErrorCodes::Unauthorized
     )]
-    pub token_mint: Box<Account<'info, Mint>>,
+    pub job: Box<Account<'info, Job>>,
+
+    // Proposed owner (signer)
+    pub new_owner: Signer<'info>,
+}
 
+// Context for a sponsored job deposit
+#[derive(Accounts)]
+#[instruction(job_index: u128)]
+pub struct JobSponsorDeposit<'info> {
     #[account(
-        mut,
-        seeds = [b"job_token", token_mint.key().as_ref()],
-        bump,
-        token::mint = token_mint,
-        token::authority = program_token_account
+        seeds = [b"market"],
+        bump
     )]
-    pub program_token_account: Box<Account<'info, TokenAccount>>,
-
-    #[account(mut)]
-    pub user_token_account: Box<Account<'info, TokenAccount>>,
+    pub market: Box<Account<'info, Market>>,
 
     #[account(
         mut,
-        constraint = provider_token_account.owner == provider
+        seeds = [b"job", job_index.to_le_bytes().as_ref()], // Use job_index as seed
+        bump,
+        constraint = job.index == job_index
     )]
-    pub provider_token_account: Box<Account<'info, TokenAccount>>,
+    pub job: Box<Account<'info, Job>>,
 
     #[account(
-        mut,
-        // constraint = credit_mint.key() == market.credit_mint
+        init,
+        payer = sponsor,
+        space = 8 + JobSponsorship::INIT_SPACE,
+        seeds = [b"job_sponsor", job.key().as_ref(), job.sponsor_seq.to_le_bytes().as_ref()],
+        bump
     )]
-    pub credit_mint: Box<Account<'info, Mint>>,
+    pub sponsorship: Box<Account<'info, JobSponsorship>>,
 
     #[account(
-        init_if_needed,
-        payer = owner,
-        seeds = [b"credit_token", credit_mint.key().as_ref()],
-        bump,
-        token::mint = credit_mint,
-        token::authority = program_credit_token_account
-        // mut,
-        // constraint = program_credit_token_account.owner == system_program.key()
+        constraint = token_mint.key() == market.token_mint @ ErrorCodes::InvalidMint
     )]
-    pub program_credit_token_account: Box<Account<'info, TokenAccount>>,
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut, seeds = [b"job_token", token_mint.key().as_ref()], bump)]
+    pub program_token_account: Box<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
-        constraint = user_credit_token_account.owner == owner.key()
+        token::mint = token_mint,
+        token::authority = sponsor
     )]
-    pub user_credit_token_account: Box<Account<'info, TokenAccount>>,
+    pub sponsor_token_account: Box<Account<'info, TokenAccount>>,
 
-    
-    /// CHECK: account is verified in credit program
-    #[account(mut)]
-    pub state: UncheckedAccount<'info>,
-    /// CHECK: account is verified in credit program
     #[account(mut)]
-    pub credit_program_usdc_token_account: UncheckedAccount<'info>,
+    pub sponsor: Signer<'info>,
 
     pub token_program: Program<'info, Token>,
-    pub credit_program: Program<'info, OysterCredits>,
     pub system_program: Program<'info, System>,
 }
 
-// Context for settling a job
+// Context for refunding a sponsorship
 #[derive(Accounts)]
 #[instruction(job_index: u128)]
-pub struct JobSettle<'info> {
+pub struct JobSponsorRefund<'info> {
     #[account(
-        mut,
         seeds = [b"market"],
         bump
     )]
-    pub market: Account<'info, Market>,
+    pub market: Box<Account<'info, Market>>,
 
     #[account(
         mut,
@@ -1194,57 +4896,66 @@ pub struct JobSettle<'info> {
         bump,
         constraint = job.index == job_index
     )]
-    pub job: Account<'info, Job>,
-
-    #[account(mut)]
-    pub token_mint: Account<'info, Mint>,
-
-    #[account(mut, seeds = [b"job_token", token_mint.key().as_ref()], bump)]
-    pub program_token_account: Account<'info, TokenAccount>,
+    pub job: Box<Account<'info, Job>>,
 
     #[account(
         mut,
-        constraint = provider_token_account.owner == job.provider,
+        close = sponsor,
+        seeds = [b"job_sponsor", job.key().as_ref(), sponsorship.seq.to_le_bytes().as_ref()],
+        bump
     )]
-    pub provider_token_account: Account<'info, TokenAccount>,
+    pub sponsorship: Box<Account<'info, JobSponsorship>>,
+
+    /// CHECK: receives the sponsorship rent
+    #[account(mut, address = sponsorship.sponsor @ ErrorCodes::Unauthorized)]
+    pub sponsor: UncheckedAccount<'info>,
 
     #[account(
-        mut,
-        // constraint = credit_mint.key() == market.credit_mint
+        constraint = token_mint.key() == market.token_mint @ ErrorCodes::InvalidMint
     )]
-    pub credit_mint: Account<'info, Mint>,
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut, seeds = [b"job_token", token_mint.key().as_ref()], bump)]
+    pub program_token_account: Box<Account<'info, TokenAccount>>,
 
     #[account(
-        init_if_needed,
-        payer = owner,
-        seeds = [b"credit_token", credit_mint.key().as_ref()],
-        bump,
-        token::mint = credit_mint,
-        token::authority = program_credit_token_account
-        // mut,
-        // constraint = program_credit_token_account.owner == system_program.key()
+        mut,
+        token::mint = token_mint,
+        constraint = sponsor_token_account.owner == sponsorship.sponsor @ ErrorCodes::Unauthorized
     )]
-    pub program_credit_token_account: Account<'info, TokenAccount>,
+    pub sponsor_token_account: Box<Account<'info, TokenAccount>>,
 
-    #[account(mut)]
-    pub owner: Signer<'info>,
+    // Sponsor, job owner, or anyone once the job is winding down
+    pub authority: Signer<'info>,
 
-    /// CHECK: account is verified in credit program
-    #[account(mut)]
-    pub state: UncheckedAccount<'info>,
-    /// CHECK: account is verified in credit program
-    #[account(mut)]
-    pub credit_program_usdc_token_account: UncheckedAccount<'info>,
+    // Holder's job NFT account, required for tokenized jobs
+    pub job_nft_account: Option<Box<Account<'info, TokenAccount>>>,
 
-    pub credit_program: Program<'info, OysterCredits>,
     pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
 }
 
-// Context for closing a job
+// Context for read-only job views
 #[derive(Accounts)]
 #[instruction(job_index: u128)]
-pub struct JobClose<'info> { 
+pub struct JobView<'info> {
+    #[account(
+        seeds = [b"market"],
+        bump
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        seeds = [b"job", job_index.to_le_bytes().as_ref()], // Use job_index as seed
+        bump,
+        constraint = job.index == job_index
+    )]
+    pub job: Box<Account<'info, Job>>,
+}
+
+// Context for finalizing a job close or provider termination, or reaping an exhausted job
+#[derive(Accounts)]
+#[instruction(job_index: u128)]
+pub struct JobFinalize<'info> {
     #[account(
         mut,
         seeds = [b"market"],
@@ -1254,57 +4965,83 @@ pub struct JobClose<'info> {
 
     #[account(
         mut,
-        close = owner,
         seeds = [b"job", job_index.to_le_bytes().as_ref()], // Use job_index as seed
         bump,
         constraint = job.index == job_index
-    )] // Close the job account and refund rent to the owner
+    )] // Closed or tombstoned by the instruction, rent refunded to the owner
     pub job: Box<Account<'info, Job>>,
 
+    /// CHECK: job top-up policy, closed along with the job if it was set up
+    #[account(
+        mut,
+        seeds = [b"topup_policy", job.key().as_ref()],
+        bump
+    )]
+    pub topup_policy: UncheckedAccount<'info>,
+
+    /// CHECK: receives the job rent, must be the job owner
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+
+    // Job NFT, retired when a tokenized job is closed
+    #[account(mut, address = job.nft_mint @ ErrorCodes::InvalidMint)]
+    pub job_nft_mint: Option<Box<Account<'info, Mint>>>,
+
+    // Holder's job NFT account, required for tokenized jobs
+    #[account(mut)]
+    pub job_nft_account: Option<Box<Account<'info, TokenAccount>>>,
+
     #[account(mut)]
     pub token_mint: Box<Account<'info, Mint>>,
 
     #[account(mut, seeds = [b"job_token", token_mint.key().as_ref()], bump)]
     pub program_token_account: Box<Account<'info, TokenAccount>>,
 
+    #[account(mut)]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
     #[account(
         mut,
-        constraint = user_token_account.owner == job.owner,
+        seeds = [b"provider", job.provider.as_ref()],
+        bump
     )]
-    pub user_token_account: Box<Account<'info, TokenAccount>>,
+    pub provider_account: Box<Account<'info, Provider>>,
 
     #[account(
         mut,
-        constraint = provider_token_account.owner == job.provider
+        constraint = provider_token_account.owner == provider_account.owner
     )]
-    pub provider_token_account: Account<'info, TokenAccount>,
+    pub provider_token_account: Box<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
-        // constraint = credit_mint.key() == market.credit_mint
+        constraint = credit_mint.key() == market.credit_mint @ ErrorCodes::InvalidMint
     )]
-    pub credit_mint: Account<'info, Mint>,
+    pub credit_mint: Box<Account<'info, Mint>>,
 
     #[account(
         init_if_needed,
-        payer = owner,
+        payer = authority,
         seeds = [b"credit_token", credit_mint.key().as_ref()],
         bump,
         token::mint = credit_mint,
         token::authority = program_credit_token_account
-        // mut,
-        // constraint = program_credit_token_account.owner == system_program.key()
     )]
-    pub program_credit_token_account: Account<'info, TokenAccount>,
+    pub program_credit_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = credit_mint)]
+    pub user_credit_token_account: Box<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
-        constraint = user_credit_token_account.owner == owner.key()
+        constraint = issuer_credit_token_account.owner == market.credit_issuer @ ErrorCodes::InvalidCreditIssuer,
+        constraint = issuer_credit_token_account.mint == credit_mint.key() @ ErrorCodes::InvalidMint
     )]
-    pub user_credit_token_account: Account<'info, TokenAccount>,
+    pub issuer_credit_token_account: Option<Box<Account<'info, TokenAccount>>>,
 
+    // Job owner or provider, anyone when reaping
     #[account(mut)]
-    pub owner: Signer<'info>, // Owner must sign the transaction
+    pub authority: Signer<'info>,
 
     /// CHECK: account is verified in credit program
     #[account(mut)]
@@ -1312,6 +5049,16 @@ pub struct JobClose<'info> {
     /// CHECK: account is verified in credit program
     #[account(mut)]
     pub credit_program_usdc_token_account: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(mut)]
+    pub credit_program_reserve: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(
+        seeds = [b"provider_tier", job.provider.as_ref()],
+        bump,
+        seeds::program = credit_program.key()
+    )]
+    pub credit_program_provider_tier: UncheckedAccount<'info>,
 
     pub credit_program: Program<'info, OysterCredits>,
     pub token_program: Program<'info, Token>,
@@ -1345,9 +5092,15 @@ pub struct JobDeposit<'info> {
     #[account(mut)]
     pub owner_token_account: Box<Account<'info, TokenAccount>>,
 
+    #[account(
+        seeds = [b"provider", job.provider.as_ref()],
+        bump
+    )]
+    pub provider_account: Box<Account<'info, Provider>>,
+
     #[account(
         mut,
-        constraint = provider_token_account.owner == job.provider,
+        constraint = provider_token_account.owner == provider_account.owner,
     )]
     pub provider_token_account: Box<Account<'info, TokenAccount>>,
 
@@ -1387,6 +5140,16 @@ pub struct JobDeposit<'info> {
     /// CHECK: account is verified in credit program
     #[account(mut)]
     pub credit_program_usdc_token_account: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(mut)]
+    pub credit_program_reserve: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(
+        seeds = [b"provider_tier", job.provider.as_ref()],
+        bump,
+        seeds::program = credit_program.key()
+    )]
+    pub credit_program_provider_tier: UncheckedAccount<'info>,
 
     pub credit_program: Program<'info, OysterCredits>,
     pub token_program: Program<'info, Token>,
@@ -1414,12 +5177,21 @@ pub struct JobWithdraw<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
+    // Holder's job NFT account, required for tokenized jobs
+    pub job_nft_account: Option<Box<Account<'info, TokenAccount>>>,
+
     #[account(mut)]
     pub token_mint: Box<Account<'info, Mint>>,
 
+    #[account(
+        seeds = [b"provider", job.provider.as_ref()],
+        bump
+    )]
+    pub provider_account: Box<Account<'info, Provider>>,
+
     #[account(
         mut,
-        constraint = provider_token_account.owner == job.provider,
+        constraint = provider_token_account.owner == provider_account.owner,
     )]
     pub provider_token_account: Box<Account<'info, TokenAccount>>,
 
@@ -1453,12 +5225,29 @@ pub struct JobWithdraw<'info> {
     )]
     pub user_credit_token_account: Account<'info, TokenAccount>,
 
+    #[account(
+        mut,
+        constraint = issuer_credit_token_account.owner == market.credit_issuer @ ErrorCodes::InvalidCreditIssuer,
+        constraint = issuer_credit_token_account.mint == credit_mint.key() @ ErrorCodes::InvalidMint
+    )]
+    pub issuer_credit_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
     /// CHECK: account is verified in credit program
     #[account(mut)]
     pub state: UncheckedAccount<'info>,
     /// CHECK: account is verified in credit program
     #[account(mut)]
     pub credit_program_usdc_token_account: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(mut)]
+    pub credit_program_reserve: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(
+        seeds = [b"provider_tier", job.provider.as_ref()],
+        bump,
+        seeds::program = credit_program.key()
+    )]
+    pub credit_program_provider_tier: UncheckedAccount<'info>,
 
     pub credit_program: Program<'info, OysterCredits>,
     pub token_program: Program<'info, Token>,
@@ -1486,6 +5275,9 @@ pub struct JobReviseRate<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
+    // Holder's job NFT account, required for tokenized jobs
+    pub job_nft_account: Option<Box<Account<'info, TokenAccount>>>,
+
     #[account(
         mut,
         constraint = token_mint.key() == market.token_mint
@@ -1501,9 +5293,22 @@ pub struct JobReviseRate<'info> {
     )]
     pub program_token_account: Box<Account<'info, TokenAccount>>,
 
+    #[account(
+        seeds = [b"provider", job.provider.as_ref()],
+        bump
+    )]
+    pub provider_account: Box<Account<'info, Provider>>,
+
+    // Provider's rate card, required when the provider publishes one
+    #[account(
+        seeds = [b"rate_card", provider_account.key().as_ref()],
+        bump
+    )]
+    pub rate_card: Option<Box<Account<'info, RateCard>>>,
+
     #[account(
         mut,
-        constraint = provider_token_account.owner == job.provider
+        constraint = provider_token_account.owner == provider_account.owner
     )]
     pub provider_token_account: Box<Account<'info, TokenAccount>>,
 
@@ -1531,6 +5336,16 @@ pub struct JobReviseRate<'info> {
     /// CHECK: account is verified in credit program
     #[account(mut)]
     pub credit_program_usdc_token_account: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(mut)]
+    pub credit_program_reserve: UncheckedAccount<'info>,
+    /// CHECK: account is verified in credit program
+    #[account(
+        seeds = [b"provider_tier", job.provider.as_ref()],
+        bump,
+        seeds::program = credit_program.key()
+    )]
+    pub credit_program_provider_tier: UncheckedAccount<'info>,
 
     pub credit_program: Program<'info, OysterCredits>,
     pub token_program: Program<'info, Token>,
@@ -1557,6 +5372,22 @@ pub struct JobMetadataUpdate<'info> {
 
     #[account(mut)]
     pub owner: Signer<'info>,
+
+    // Holder's job NFT account, required for tokenized jobs
+    pub job_nft_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        seeds = [b"provider", job.provider.as_ref()],
+        bump
+    )]
+    pub provider_account: Box<Account<'info, Provider>>,
+
+    // Provider's rate card, required when the provider publishes one
+    #[account(
+        seeds = [b"rate_card", provider_account.key().as_ref()],
+        bump
+    )]
+    pub rate_card: Option<Box<Account<'info, RateCard>>>,
 }
 // Events
 #[event]
@@ -1570,12 +5401,134 @@ pub struct JobSettlementWithdrawn {
     pub job: Pubkey,
     pub token: Pubkey,
     pub provider: Pubkey,
-    pub amount: u64,
+    pub face_amount: u64,
+    pub paid_amount: u64,
 }
 
 #[event]
 pub struct ProviderRemoved {
     pub provider: Pubkey,
+    pub active_jobs: u64,
+}
+
+#[event]
+pub struct ProviderClosed {
+    pub provider: Pubkey,
+}
+
+#[event]
+pub struct ProviderPayoutUpdated {
+    pub provider: Pubkey,
+    pub beneficiaries: Vec<Beneficiary>,
+}
+
+#[event]
+pub struct ProviderApplied {
+    pub applicant: Pubkey,
+    pub cp: String,
+}
+
+#[event]
+pub struct ProviderApplicationApproved {
+    pub applicant: Pubkey,
+}
+
+#[event]
+pub struct ProviderApplicationRejected {
+    pub applicant: Pubkey,
+}
+
+#[event]
+pub struct ProviderSuspended {
+    pub provider: Pubkey,
+}
+
+#[event]
+pub struct ProviderReinstated {
+    pub provider: Pubkey,
+}
+
+#[event]
+pub struct MarketPermissionedUpdated {
+    pub permissioned: bool,
+}
+
+#[event]
+pub struct ProviderBonded {
+    pub provider: Pubkey,
+    pub amount: u64,
+    pub bond: u64,
+}
+
+#[event]
+pub struct ProviderUnbondRequested {
+    pub provider: Pubkey,
+    pub amount: u64,
+    pub unbond_at: i64,
+}
+
+#[event]
+pub struct ProviderUnbonded {
+    pub provider: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ProviderSlashed {
+    pub provider: Pubkey,
+    pub job: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BondConfigUpdated {
+    pub min_provider_bond: u64,
+    pub unbonding_period: u64,
+}
+
+#[event]
+pub struct ProviderKeyRotationStarted {
+    pub provider: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct ProviderKeyRotated {
+    pub provider: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct ProviderOperatorUpdated {
+    pub provider: Pubkey,
+    pub operator: Pubkey,
+    pub permissions: u8,
+}
+
+#[event]
+pub struct ProviderOperatorRemoved {
+    pub provider: Pubkey,
+    pub operator: Pubkey,
+}
+
+#[event]
+pub struct ProviderProfileUpdated {
+    pub provider: Pubkey,
+    pub version: u64,
+    pub cps: Option<Vec<String>>,
+    pub regions: Option<Vec<String>>,
+    pub instance_types: Option<Vec<String>>,
+    pub attestation_key: Option<Pubkey>,
+    pub metadata_uri: Option<String>,
+}
+
+#[event]
+pub struct ProviderRateCardUpdated {
+    pub provider: Pubkey,
+    pub entries: Vec<RateCardEntry>,
+    pub effective_at: i64,
 }
 
 #[event]
@@ -1596,8 +5549,10 @@ pub struct JobOpened {
     pub metadata: String, // Now a String
     pub owner: Pubkey,
     pub provider: Pubkey,
+    pub provider_cp: String,
     pub rate: u64,
     pub balance: u64,
+    pub accept_deadline: u64,
     pub timestamp: i64,
 }
 
@@ -1613,6 +5568,140 @@ pub struct JobClosed {
     pub job: Pubkey,
 }
 
+#[event]
+pub struct JobStatusChanged {
+    pub job: Pubkey,
+    pub old_status: JobStatus,
+    pub new_status: JobStatus,
+}
+
+#[event]
+pub struct TombstoneJobsUpdated {
+    pub tombstone_jobs: bool,
+}
+
+#[event]
+pub struct JobSponsored {
+    pub job: Pubkey,
+    pub sponsor: Pubkey,
+    pub seq: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct JobSponsorRefunded {
+    pub job: Pubkey,
+    pub sponsor: Pubkey,
+    pub seq: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct JobSponsorRefundPolicyUpdated {
+    pub job: Pubkey,
+    pub policy: SponsorRefundPolicy,
+}
+
+#[event]
+pub struct JobTopupPolicyUpdated {
+    pub job: Pubkey,
+    pub target_runway: u64,
+    pub max_per_topup: u64,
+    pub monthly_cap: u64,
+}
+
+#[event]
+pub struct JobTopupPolicyRemoved {
+    pub job: Pubkey,
+}
+
+#[event]
+pub struct JobAutoToppedUp {
+    pub job: Pubkey,
+    pub keeper: Pubkey,
+    pub amount: u64,
+    pub runway: u64,
+}
+
+#[event]
+pub struct JobLowBalance {
+    pub job: Pubkey,
+    pub runway: u64,
+    pub runway_threshold: u64,
+    pub below_notice: bool,
+}
+
+#[event]
+pub struct JobRunwayThresholdUpdated {
+    pub job: Pubkey,
+    pub runway_threshold: u64,
+}
+
+#[event]
+pub struct JobReaped {
+    pub job: Pubkey,
+    pub provider: Pubkey,
+    pub keeper: Pubkey,
+    pub refunded: u64,
+    pub bounty: u64,
+}
+
+#[event]
+pub struct MinSponsorDepositUpdated {
+    pub min_sponsor_deposit: u64,
+}
+
+#[event]
+pub struct ReapBountyUpdated {
+    pub reap_bounty: u64,
+}
+
+#[event]
+pub struct JobCloseRequested {
+    pub job: Pubkey,
+    pub close_at: u64,
+}
+
+#[event]
+pub struct JobAccepted {
+    pub job: Pubkey,
+    pub provider: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct JobRejected {
+    pub job: Pubkey,
+    pub provider: Pubkey,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct JobExpired {
+    pub job: Pubkey,
+    pub provider: Pubkey,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct AcceptTimeoutUpdated {
+    pub accept_timeout: u64,
+}
+
+#[event]
+pub struct JobTerminationStarted {
+    pub job: Pubkey,
+    pub provider: Pubkey,
+    pub terminate_at: u64,
+}
+
+#[event]
+pub struct JobTerminatedByProvider {
+    pub job: Pubkey,
+    pub provider: Pubkey,
+    pub refunded: u64,
+}
+
 #[event]
 pub struct JobDeposited {
     pub job: Pubkey,
@@ -1628,12 +5717,60 @@ pub struct JobWithdrew {
     pub amount: u64,
 }
 
+#[event]
+pub struct JobCreditRefunded {
+    pub job: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct JobCreditReturnedToIssuer {
+    pub job: Pubkey,
+    pub issuer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct JobCreditBurned {
+    pub job: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CreditRefundPolicyUpdated {
+    pub policy: CreditRefundPolicy,
+    pub credit_issuer: Pubkey,
+}
+
 #[event]
 pub struct JobRateRevised {
     pub job: Pubkey,
     pub new_rate: u64,
 }
 
+#[event]
+pub struct JobTokenized {
+    pub job: Pubkey,
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct JobOwnershipTransferStarted {
+    pub job: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct JobOwnershipTransferred {
+    pub job: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub balance: u64,
+    pub credit_balance: u64,
+}
+
 #[event]
 pub struct JobMetadataUpdated {
     pub job: Pubkey,
@@ -1669,4 +5806,94 @@ pub enum ErrorCodes {
     InvalidMint,
     #[msg("Unchanged metadata")]
     UnchangedMetadata,
+    #[msg("Invalid credit issuer")]
+    InvalidCreditIssuer,
+    #[msg("Provider is not active")]
+    ProviderNotActive,
+    #[msg("Provider has not been removed")]
+    ProviderNotWindingDown,
+    #[msg("Provider has active jobs")]
+    ProviderHasActiveJobs,
+    #[msg("Job is being terminated")]
+    JobTerminating,
+    #[msg("Job is not being terminated")]
+    JobNotTerminating,
+    #[msg("Notice period is not over")]
+    NoticePeriodNotOver,
+    #[msg("Invalid operator permissions")]
+    InvalidOperatorPermissions,
+    #[msg("Operator lacks the required permission")]
+    MissingOperatorPermission,
+    #[msg("Invalid provider key")]
+    InvalidProviderKey,
+    #[msg("Invalid payout configuration")]
+    InvalidPayoutConfig,
+    #[msg("Missing or invalid beneficiary token account")]
+    InvalidBeneficiaryAccount,
+    #[msg("Invalid provider profile")]
+    InvalidProfile,
+    #[msg("Invalid rate card")]
+    InvalidRateCard,
+    #[msg("Provider rate card account required")]
+    RateCardRequired,
+    #[msg("No rate card entry for the job's instance type and region")]
+    RateCardEntryNotFound,
+    #[msg("Rate is below the provider's rate card")]
+    RateBelowRateCard,
+    #[msg("Provider bond is below the market minimum")]
+    InsufficientProviderBond,
+    #[msg("Provider still has a bond")]
+    ProviderStillBonded,
+    #[msg("Nothing to unbond")]
+    NothingToUnbond,
+    #[msg("Unbonding period is not over")]
+    UnbondingPeriodNotOver,
+    #[msg("Provider registration requires an approved application")]
+    ProviderNotApproved,
+    #[msg("Provider is suspended")]
+    ProviderSuspended,
+    #[msg("Provider is not suspended")]
+    ProviderNotSuspended,
+    #[msg("Job is closed")]
+    JobClosed,
+    #[msg("Instruction is not allowed in the job's status")]
+    InvalidJobStatus,
+    #[msg("Job is closing")]
+    JobClosing,
+    #[msg("Job is not closing")]
+    JobNotClosing,
+    #[msg("Job still covers its notice period")]
+    JobNotReapable,
+    #[msg("Invalid top-up policy")]
+    InvalidTopupPolicy,
+    #[msg("Job runway is above the top-up target")]
+    TopupNotNeeded,
+    #[msg("Top-up cap reached for this period")]
+    TopupCapReached,
+    #[msg("Job has sponsorships that are not refunded")]
+    SponsorsOutstanding,
+    #[msg("Amount includes sponsored funds")]
+    SponsoredFunds,
+    #[msg("Sponsorships are refunded latest first")]
+    SponsorRefundOrder,
+    #[msg("Invalid job owner")]
+    InvalidJobOwner,
+    #[msg("Job NFT accounts are required for tokenized jobs")]
+    JobNftRequired,
+    #[msg("Tokenized jobs are transferred with their NFT")]
+    JobTokenized,
+    #[msg("Job is not pending")]
+    JobNotPending,
+    #[msg("Job acceptance deadline has passed")]
+    AcceptDeadlinePassed,
+    #[msg("Job acceptance deadline has not passed")]
+    AcceptDeadlineNotPassed,
+    #[msg("Provider has no active jobs to release")]
+    ActiveJobsUnderflow,
+    #[msg("Token account required to post a bond")]
+    BondAccountRequired,
+    #[msg("Job has the maximum number of sponsorships")]
+    TooManySponsors,
+    #[msg("Missing or invalid sponsorship accounts")]
+    InvalidSponsorAccount,
 }
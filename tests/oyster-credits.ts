@@ -0,0 +1,553 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { OysterCredits } from "../target/types/oyster_credits";
+import { MarketV } from "../target/types/market_v";
+import { createMint, getAccount, getOrCreateAssociatedTokenAccount, mintTo, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { assert, expect } from "chai";
+
+describe("oyster_credits", () => {
+    // Configure the client to use the local cluster.
+    const provider = anchor.AnchorProvider.env();
+    anchor.setProvider(provider);
+
+    const program = anchor.workspace.OysterCredits as Program<OysterCredits>;
+    const authority = provider.wallet.publicKey;
+
+    it("can initialize", async () => {
+        const [state,] = PublicKey.findProgramAddressSync([Buffer.from("state")], program.programId);
+        const [creditMint,] = PublicKey.findProgramAddressSync([Buffer.from("credit_mint")], program.programId);
+        const marketProgramId = (anchor.workspace.MarketV as Program<MarketV>).programId;
+
+        await program.methods.initialize(
+            authority,
+            marketProgramId,
+        ).accountsStrict({
+            state,
+            creditMint,
+            signer: authority,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+
+        const stateData = await program.account.state.fetch(state);
+        expect(stateData.admin.toBase58()).to.equal(authority.toBase58());
+        expect(stateData.oysterMarket.toBase58()).to.equal(marketProgramId.toBase58());
+        expect(stateData.initialized).to.be.true;
+    });
+});
+
+describe("oyster_credits - redemption rates", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<OysterCredits>;
+    let state: PublicKey;
+    let other: Keypair;
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        [state,] = PublicKey.findProgramAddressSync([Buffer.from("state")], program.programId);
+        other = Keypair.generate();
+    });
+
+    it("redeems every tier at face value by default", async () => {
+        const stateData = await program.account.state.fetch(state);
+        expect(stateData.tierRedemptionBps).to.deep.equal(new Array(8).fill(10000));
+    });
+
+    it("can set a tier redemption rate", async () => {
+        await program.methods.setTierRedemptionRate(7, 9000).accountsStrict({
+            state,
+            authority: provider.wallet.publicKey,
+        }).rpc();
+
+        expect((await program.account.state.fetch(state)).tierRedemptionBps[7]).to.equal(9000);
+
+        await program.methods.setTierRedemptionRate(7, 10000).accountsStrict({
+            state,
+            authority: provider.wallet.publicKey,
+        }).rpc();
+    });
+
+    it("cannot redeem above face value", async () => {
+        try {
+            await program.methods.setTierRedemptionRate(1, 10001).accountsStrict({
+                state,
+                authority: provider.wallet.publicKey,
+            }).rpc();
+            assert.fail("the rate should not have been set");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidRedemptionRate");
+        }
+    });
+
+    it("cannot set the rate of an unknown tier", async () => {
+        try {
+            await program.methods.setTierRedemptionRate(8, 5000).accountsStrict({
+                state,
+                authority: provider.wallet.publicKey,
+            }).rpc();
+            assert.fail("the rate should not have been set");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidTier");
+        }
+    });
+
+    it("only the admin can set rates", async () => {
+        try {
+            await program.methods.setTierRedemptionRate(1, 5000).accountsStrict({
+                state,
+                authority: other.publicKey,
+            }).signers([other]).rpc();
+            assert.fail("the rate should not have been set");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("OnlyAdmin");
+        }
+    });
+
+    it("can assign a provider tier", async () => {
+        const providerKey = Keypair.generate().publicKey;
+        const [providerTier,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider_tier"), providerKey.toBuffer()],
+            program.programId
+        );
+
+        await program.methods.setProviderTier(providerKey, 3).accountsStrict({
+            state,
+            providerTier,
+            authority: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+
+        const providerTierData = await program.account.providerTier.fetch(providerTier);
+        expect(providerTierData.provider.toBase58()).to.equal(providerKey.toBase58());
+        expect(providerTierData.tier).to.equal(3);
+
+        try {
+            await program.methods.setProviderTier(providerKey, 8).accountsStrict({
+                state,
+                providerTier,
+                authority: provider.wallet.publicKey,
+                systemProgram: SystemProgram.programId,
+            }).rpc();
+            assert.fail("the tier should not have been set");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidTier");
+        }
+    });
+
+    it("can send haircuts to the subsidy pool", async () => {
+        await program.methods.setHaircutToSubsidy(true).accountsStrict({
+            state,
+            authority: provider.wallet.publicKey,
+        }).rpc();
+        expect((await program.account.state.fetch(state)).haircutToSubsidy).to.be.true;
+
+        await program.methods.setHaircutToSubsidy(false).accountsStrict({
+            state,
+            authority: provider.wallet.publicKey,
+        }).rpc();
+        expect((await program.account.state.fetch(state)).haircutToSubsidy).to.be.false;
+    });
+});
+
+describe("oyster_credits - events", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<OysterCredits>;
+    let state: PublicKey;
+    let creditMint: PublicKey;
+    let minterRole: PublicKey;
+    let tokenAccount: PublicKey;
+
+    // Events emitted by a confirmed transaction
+    async function events(signature: string): Promise<any[]> {
+        const tx = await provider.connection.getTransaction(signature, {
+            commitment: "confirmed",
+            maxSupportedTransactionVersion: 0,
+        });
+        return [...new anchor.EventParser(program.programId, program.coder).parseLogs(tx.meta.logMessages)];
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        [state,] = PublicKey.findProgramAddressSync([Buffer.from("state")], program.programId);
+        [creditMint,] = PublicKey.findProgramAddressSync([Buffer.from("credit_mint")], program.programId);
+        [minterRole,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("minter"), provider.wallet.publicKey.toBuffer()],
+            program.programId
+        );
+        tokenAccount = (await getOrCreateAssociatedTokenAccount(
+            provider.connection,
+            (provider.wallet as anchor.Wallet).payer,
+            creditMint,
+            provider.wallet.publicKey
+        )).address;
+    });
+
+    it("numbers events in order", async () => {
+        const seq = (await program.account.state.fetch(state)).eventSeq;
+
+        let signature = await program.methods.setHaircutToSubsidy(false).accountsStrict({
+            state,
+            authority: provider.wallet.publicKey,
+        }).rpc({ commitment: "confirmed" });
+        let emitted = await events(signature);
+        expect(emitted.length).to.equal(1);
+        expect(emitted[0].name).to.equal("haircutDestinationUpdated");
+        expect(emitted[0].data.seq.toString()).to.equal(seq.addn(1).toString());
+        expect(emitted[0].data.haircutToSubsidy).to.be.false;
+
+        signature = await program.methods.setTierRedemptionRate(2, 10000).accountsStrict({
+            state,
+            authority: provider.wallet.publicKey,
+        }).rpc({ commitment: "confirmed" });
+        emitted = await events(signature);
+        expect(emitted[0].name).to.equal("tierRedemptionRateUpdated");
+        expect(emitted[0].data.seq.toString()).to.equal(seq.addn(2).toString());
+        expect(emitted[0].data.tier).to.equal(2);
+        expect(emitted[0].data.redemptionBps).to.equal(10000);
+
+        expect((await program.account.state.fetch(state)).eventSeq.toString()).to.equal(seq.addn(2).toString());
+    });
+
+    it("emits events for minting and burning credits", async () => {
+        let signature = await program.methods.addMinter(
+            provider.wallet.publicKey,
+            new anchor.BN(1000),
+            new anchor.BN(86400),
+            new anchor.BN(1000)
+        ).accountsStrict({
+            state,
+            minterRole,
+            authority: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+        }).rpc({ commitment: "confirmed" });
+        let emitted = await events(signature);
+        expect(emitted[0].name).to.equal("minterAdded");
+        expect(emitted[0].data.minter.toBase58()).to.equal(provider.wallet.publicKey.toBase58());
+        expect(emitted[0].data.role.toBase58()).to.equal(minterRole.toBase58());
+        expect(emitted[0].data.windowAllowance.toNumber()).to.equal(1000);
+
+        signature = await program.methods.mint(new anchor.BN(100)).accountsStrict({
+            state,
+            minterRole,
+            creditMint,
+            tokenAccount,
+            signer: provider.wallet.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        }).rpc({ commitment: "confirmed" });
+        emitted = await events(signature);
+        expect(emitted[0].name).to.equal("creditsMinted");
+        expect(emitted[0].data.minter.toBase58()).to.equal(provider.wallet.publicKey.toBase58());
+        expect(emitted[0].data.to.toBase58()).to.equal(tokenAccount.toBase58());
+        expect(emitted[0].data.amount.toNumber()).to.equal(100);
+
+        signature = await program.methods.burn(new anchor.BN(100)).accountsStrict({
+            state,
+            creditMint,
+            tokenAccount,
+            authority: provider.wallet.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        }).rpc({ commitment: "confirmed" });
+        emitted = await events(signature);
+        expect(emitted[0].name).to.equal("creditsBurned");
+        expect(emitted[0].data.from.toBase58()).to.equal(tokenAccount.toBase58());
+        expect(emitted[0].data.job.toBase58()).to.equal(PublicKey.default.toBase58());
+        expect(emitted[0].data.amount.toNumber()).to.equal(100);
+    });
+});
+
+describe("oyster_credits - minter roles", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<OysterCredits>;
+    let state: PublicKey;
+    let creditMint: PublicKey;
+    let tokenAccount: PublicKey;
+    let minter: Keypair;
+    let minterRole: PublicKey;
+
+    async function addMinter(windowAllowance: number, windowDuration: number, lifetimeCap: number) {
+        await program.methods.addMinter(
+            minter.publicKey,
+            new anchor.BN(windowAllowance),
+            new anchor.BN(windowDuration),
+            new anchor.BN(lifetimeCap)
+        ).accountsStrict({
+            state,
+            minterRole,
+            authority: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+    }
+
+    async function mint(signer: Keypair, amount: number) {
+        await program.methods.mint(new anchor.BN(amount)).accountsStrict({
+            state,
+            minterRole: PublicKey.findProgramAddressSync(
+                [Buffer.from("minter"), signer.publicKey.toBuffer()],
+                program.programId
+            )[0],
+            creditMint,
+            tokenAccount,
+            signer: signer.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        }).signers([signer]).rpc();
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        [state,] = PublicKey.findProgramAddressSync([Buffer.from("state")], program.programId);
+        [creditMint,] = PublicKey.findProgramAddressSync([Buffer.from("credit_mint")], program.programId);
+        tokenAccount = (await getOrCreateAssociatedTokenAccount(
+            provider.connection,
+            (provider.wallet as anchor.Wallet).payer,
+            creditMint,
+            provider.wallet.publicKey
+        )).address;
+
+        minter = Keypair.generate();
+        [minterRole,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("minter"), minter.publicKey.toBuffer()],
+            program.programId
+        );
+    });
+
+    it("can mint within the window allowance", async () => {
+        await addMinter(100, 3600, 150);
+        await mint(minter, 60);
+
+        const role = await program.account.minterRole.fetch(minterRole);
+        expect(role.minter.toBase58()).to.equal(minter.publicKey.toBase58());
+        expect(role.windowMinted.toNumber()).to.equal(60);
+        expect(role.lifetimeMinted.toNumber()).to.equal(60);
+        expect(role.revoked).to.be.false;
+    });
+
+    it("cannot mint past the window allowance", async () => {
+        try {
+            await mint(minter, 50);
+            assert.fail("the mint should have exceeded the window allowance");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("MintAllowanceExceeded");
+        }
+    });
+
+    it("cannot mint past the lifetime cap", async () => {
+        // granting the role again resets it
+        await addMinter(100, 1, 150);
+        let role = await program.account.minterRole.fetch(minterRole);
+        expect(role.windowMinted.toNumber()).to.equal(0);
+        expect(role.lifetimeMinted.toNumber()).to.equal(0);
+
+        await mint(minter, 100);
+        // wait for a new window
+        await new Promise((resolve) => setTimeout(resolve, 2000));
+
+        try {
+            await mint(minter, 60);
+            assert.fail("the mint should have exceeded the lifetime cap");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("MintCapExceeded");
+        }
+
+        await mint(minter, 50);
+        role = await program.account.minterRole.fetch(minterRole);
+        expect(role.windowMinted.toNumber()).to.equal(50);
+        expect(role.lifetimeMinted.toNumber()).to.equal(150);
+    });
+
+    it("cannot grant a role without a mint window", async () => {
+        try {
+            await addMinter(100, 0, 150);
+            assert.fail("the role should not have been granted");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidMintWindow");
+        }
+    });
+
+    it("only the admin can grant roles", async () => {
+        const other = Keypair.generate();
+        try {
+            await program.methods.addMinter(
+                minter.publicKey,
+                new anchor.BN(100),
+                new anchor.BN(3600),
+                new anchor.BN(150)
+            ).accountsStrict({
+                state,
+                minterRole,
+                authority: other.publicKey,
+                systemProgram: SystemProgram.programId,
+            }).signers([other]).rpc();
+            assert.fail("the role should not have been granted");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("OnlyAdmin");
+        }
+    });
+
+    it("cannot mint without a role", async () => {
+        try {
+            await mint(Keypair.generate(), 1);
+            assert.fail("minting without a role should fail");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("AccountNotInitialized");
+        }
+    });
+
+    it("revoked minters cannot mint", async () => {
+        await program.methods.revokeMinter().accountsStrict({
+            state,
+            minterRole,
+            authority: provider.wallet.publicKey,
+        }).rpc();
+        expect((await program.account.minterRole.fetch(minterRole)).revoked).to.be.true;
+
+        try {
+            await mint(minter, 1);
+            assert.fail("a revoked minter should not mint");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("MinterRevoked");
+        }
+
+        try {
+            await program.methods.revokeMinter().accountsStrict({
+                state,
+                minterRole,
+                authority: provider.wallet.publicKey,
+            }).rpc();
+            assert.fail("the role is already revoked");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("MinterRevoked");
+        }
+    });
+});
+
+describe("oyster_credits - reserves", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<OysterCredits>;
+    let payer: Keypair;
+    let state: PublicKey;
+    let creditMint: PublicKey;
+    let usdcMint: PublicKey;
+    let reserve: PublicKey;
+    let programUsdcTokenAccount: PublicKey;
+    let payerTokenAccount: PublicKey;
+
+    function reserveAccounts(mint: PublicKey, authority: PublicKey): any {
+        return {
+            state,
+            usdcMint: mint,
+            creditMint,
+            reserve: PublicKey.findProgramAddressSync([Buffer.from("reserve"), mint.toBuffer()], program.programId)[0],
+            programUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("program_usdc"), mint.toBuffer()],
+                program.programId
+            )[0],
+            authority,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        };
+    }
+
+    async function withdrawReserve(amount: number) {
+        await program.methods.withdrawReserve(new anchor.BN(amount)).accountsStrict({
+            state,
+            usdcMint,
+            reserve,
+            programUsdcTokenAccount,
+            receiver: payerTokenAccount,
+            authority: payer.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        }).rpc();
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+        [state,] = PublicKey.findProgramAddressSync([Buffer.from("state")], program.programId);
+        [creditMint,] = PublicKey.findProgramAddressSync([Buffer.from("credit_mint")], program.programId);
+
+        // a second stablecoin backing credits
+        usdcMint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+        [reserve,] = PublicKey.findProgramAddressSync([Buffer.from("reserve"), usdcMint.toBuffer()], program.programId);
+        [programUsdcTokenAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("program_usdc"), usdcMint.toBuffer()],
+            program.programId
+        );
+        payerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, usdcMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, usdcMint, payerTokenAccount, payer, 1000);
+    });
+
+    it("can add a reserve", async () => {
+        await program.methods.addReserve().accountsStrict(reserveAccounts(usdcMint, payer.publicKey)).rpc();
+
+        const reserveData = await program.account.reserve.fetch(reserve);
+        expect(reserveData.mint.toBase58()).to.equal(usdcMint.toBase58());
+        expect(reserveData.vault.toBase58()).to.equal(programUsdcTokenAccount.toBase58());
+        expect(reserveData.totalDeposited.toNumber()).to.equal(0);
+    });
+
+    it("cannot add a reserve in other decimals", async () => {
+        const mint = await createMint(provider.connection, payer, payer.publicKey, null, 9);
+        try {
+            await program.methods.addReserve().accountsStrict(reserveAccounts(mint, payer.publicKey)).rpc();
+            assert.fail("the reserve should not have been added");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("ReserveDecimalsMismatch");
+        }
+    });
+
+    it("only the admin can add reserves", async () => {
+        const other = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(other.publicKey, 10 ** 9)
+        );
+        const mint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+        try {
+            await program.methods.addReserve().accountsStrict(reserveAccounts(mint, other.publicKey))
+                .signers([other]).rpc();
+            assert.fail("the reserve should not have been added");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("OnlyAdmin");
+        }
+    });
+
+    it("can deposit into and withdraw from a reserve", async () => {
+        await program.methods.depositReserve(new anchor.BN(1000)).accountsStrict({
+            state,
+            usdcMint,
+            reserve,
+            programUsdcTokenAccount,
+            depositorTokenAccount: payerTokenAccount,
+            depositor: payer.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        }).rpc();
+        expect((await program.account.reserve.fetch(reserve)).totalDeposited.toNumber()).to.equal(1000);
+        expect((await getAccount(provider.connection, programUsdcTokenAccount)).amount.toString()).to.equal("1000");
+
+        await withdrawReserve(400);
+        expect((await program.account.reserve.fetch(reserve)).totalWithdrawn.toNumber()).to.equal(400);
+        expect((await getAccount(provider.connection, payerTokenAccount)).amount.toString()).to.equal("400");
+
+        try {
+            await withdrawReserve(700);
+            assert.fail("the reserve should not have been overdrawn");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("NotEnoughUSDC");
+        }
+
+        try {
+            await withdrawReserve(0);
+            assert.fail("nothing should have been withdrawn");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidAmount");
+        }
+    });
+});
@@ -1,17 +1,36 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { MarketV } from "../target/types/market_v";
-import { createAssociatedTokenAccount, createMint, getAccount, getOrCreateAssociatedTokenAccount, mintTo, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { OysterCredits } from "../target/types/oyster_credits";
+import { approve, ASSOCIATED_TOKEN_PROGRAM_ID, createAssociatedTokenAccount, createMint, getAccount, getAssociatedTokenAddressSync, getMint, getOrCreateAssociatedTokenAccount, mintTo, TOKEN_PROGRAM_ID, transfer } from "@solana/spl-token";
 import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
 import { assert, expect } from "chai";
-import { BankrunProvider, startAnchor } from "anchor-bankrun";
-import { Clock, start } from "solana-bankrun";
 // import { OysterSolanaContracts } from "../target/types/oyster_solana_contracts";
 
 function get_seeds(seed_str: any): any {
     return [...seed_str].map((char) => char.codePointAt());
 }
 
+// Accounts for registering a provider without a bond in the current market token
+async function providerAddAccounts(program: Program<MarketV>, providerAccount: PublicKey, authority: PublicKey): Promise<any> {
+    const market = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId)[0];
+    const tokenMint = (await program.account.market.fetch(market)).tokenMint;
+    return {
+        market,
+        provider: providerAccount,
+        application: null,
+        tokenMint,
+        bondTokenAccount: PublicKey.findProgramAddressSync(
+            [Buffer.from("provider_bond"), tokenMint.toBuffer()],
+            program.programId
+        )[0],
+        authorityTokenAccount: null,
+        authority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+    };
+}
+
 describe("market_v1", () => {
     // Configure the client to use the local cluster.
     const provider = anchor.AnchorProvider.env();
@@ -19,10 +38,12 @@ describe("market_v1", () => {
 
     // add test to initialize the MarketV program
     const program = anchor.workspace.MarketV as Program<MarketV>;
+    const creditProgram = anchor.workspace.OysterCredits as Program<OysterCredits>;
     const authority = provider.wallet.publicKey;
     // (provider.wallet as anchor.Wallet).payer
 
     let tokenMint: PublicKey;
+    let creditMint: PublicKey;
     let waitTime = new anchor.BN(60);
 
     // let tokenMint;
@@ -35,14 +56,36 @@ describe("market_v1", () => {
             null,
             6 // USDC typically has 6 decimal places
         );
+
+        // the market redeems credits of the oyster credits program
+        [creditMint,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("credit_mint")],
+            creditProgram.programId
+        );
+        const [state,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("state")],
+            creditProgram.programId
+        );
+        if (!(await creditProgram.account.state.fetchNullable(state))) {
+            await creditProgram.methods.initialize(
+                authority,
+                program.programId,
+            ).accountsStrict({
+                state,
+                creditMint,
+                signer: authority,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            }).rpc();
+        }
     })
 
     it("can initialize", async () => {
-        const RATE_LOCK_SELECTOR = "RATE_LOCK";
         const tx = await program.methods.initialize(
-            RATE_LOCK_SELECTOR,
-            waitTime,
             authority,
+            waitTime,
+            creditProgram.programId,
+            creditMint,
         ).accounts({
             admin: authority,
             tokenMint,
@@ -58,8 +101,9 @@ describe("market_v1", () => {
         let marketData = await program.account.market.fetch(marketAccount);
 
         expect(marketData.admin.toBase58()).to.equal(authority.toBase58());
-        expect(marketData.jobIndex.toString()).to.equal(new anchor.BN(0).toString());
+        expect(marketData.jobIndex.toString()).to.equal(new anchor.BN("ffffffffffffffff", 16).shln(64).toString());
         expect(marketData.tokenMint.toBase58()).to.equal(tokenMint.toBase58());
+        expect(marketData.creditMint.toBase58()).to.equal(creditMint.toBase58());
     });
 });
 
@@ -86,12 +130,11 @@ describe("market_v1 - add provider", () => {
     it("can add provider", async () => {
         let cp = "https://example.com/";
         await program.methods.providerAdd(
-            cp
-        ).accountsStrict({
-            provider: providerAccount,
-            authority,
-            systemProgram: SystemProgram.programId,
-        }).rpc();
+            cp,
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, authority)
+        ).rpc();
 
         const provider = await program.account.provider.fetch(providerAccount);
 
@@ -99,19 +142,30 @@ describe("market_v1 - add provider", () => {
     });
 
     it("cannot add provider with empty cp", async () => {
+        // a fresh authority, the wallet is already registered
+        const provider = anchor.AnchorProvider.env();
+        const newAuthority = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(newAuthority.publicKey, 10 ** 9)
+        );
+        let [newProviderAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), newAuthority.publicKey.toBuffer()],
+            program.programId
+        );
+
         let cp = "";
         // check for fail transaction
         try {
             await program.methods.providerAdd(
-                cp
-            ).accountsStrict({
-                provider: providerAccount,
-                authority,
-                systemProgram: SystemProgram.programId,
-            }).signers([]).rpc();
+                cp,
+                new anchor.BN(0)
+            ).accountsStrict(
+                await providerAddAccounts(program, newProviderAccount, newAuthority.publicKey)
+            ).signers([newAuthority]).rpc();
+            assert.fail("The provider should not have been added");
         } catch (error) {
             console.log("error: ", error?.error);
-            expect((error as anchor.AnchorError).message).to.be.an('ProviderAlreadyExists');
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidControlPlaneUrl");
         }
     });
 
@@ -120,7 +174,7 @@ describe("market_v1 - add provider", () => {
 describe("market_v1 - remove provider", () => {
     let providerAccount: PublicKey;
     let program: Program<MarketV>;
-    let authority: PublicKey;
+    let authority: Keypair;
 
     before(async () => {
         // Configure the client to use the local cluster.
@@ -129,29 +183,51 @@ describe("market_v1 - remove provider", () => {
 
         // add test to initialize the MarketV program
         program = anchor.workspace.MarketV as Program<MarketV>;
-        authority = provider.wallet.publicKey;
+        // the wallet provider is kept for the job tests
+        authority = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(authority.publicKey, 10 ** 9)
+        );
         [providerAccount,] = PublicKey.findProgramAddressSync(
-            [Buffer.from("provider"), authority.toBuffer()],
+            [Buffer.from("provider"), authority.publicKey.toBuffer()],
             program.programId
         );
         console.log("providerAccount", providerAccount.toBase58());
 
         let cp = "https://example.com/";
         await program.methods.providerAdd(
-            cp
-        ).accountsStrict({
-            provider: providerAccount,
-            authority,
-            systemProgram: SystemProgram.programId,
-        }).rpc();
+            cp,
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, authority.publicKey)
+        ).signers([authority]).rpc();
     })
 
     it("can remove provider", async () => {
         await program.methods.providerRemove().accountsStrict({
             provider: providerAccount,
-            authority,
+            authority: authority.publicKey,
             // systemProgram: SystemProgram.programId,
-        }).rpc();
+        }).signers([authority]).rpc();
+
+        // removal only winds the provider down
+        const provider = await program.account.provider.fetch(providerAccount);
+        expect(provider.status).to.have.property("windingDown");
+
+        // with no active jobs the account can be closed
+        // profile and rate card are closed along with the provider when they exist
+        await program.methods.providerClose().accountsStrict({
+            provider: providerAccount,
+            profile: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider_profile"), providerAccount.toBuffer()],
+                program.programId
+            )[0],
+            rateCard: PublicKey.findProgramAddressSync(
+                [Buffer.from("rate_card"), providerAccount.toBuffer()],
+                program.programId
+            )[0],
+            authority: authority.publicKey,
+        }).signers([authority]).rpc();
 
         // verify that the provider is removed
         try {
@@ -164,28 +240,35 @@ describe("market_v1 - remove provider", () => {
 
     it("cannot remove provider if not the authority", async () => {
         // Create a new provider account
-        // let providerAccount1: PublicKey;
-        // [providerAccount1,] = PublicKey.findProgramAddressSync(
-        //     [Buffer.from("provider"), authority.toBuffer()],
-        //     program.programId
-        // );
+        const provider = anchor.AnchorProvider.env();
+        const authority1 = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(authority1.publicKey, 10 ** 9)
+        );
+        let providerAccount1: PublicKey;
+        [providerAccount1,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), authority1.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount1, authority1.publicKey)
+        ).signers([authority1]).rpc();
 
         // check for fail transaction
-        await program.methods.providerRemove().accounts({
-            // provider: providerAccount1,
-            authority,
-            // systemProgram: SystemProgram.programId,
-        }).rpc();
-        // try {
-        //     await program.methods.providerRemove().accounts({
-        //         // provider: providerAccount1,
-        //         authority,
-        //         // systemProgram: SystemProgram.programId,
-        //     }).rpc();
-        // } catch (error) {
-        //     console.log("error: ", error?.error);
-        //     expect((error as anchor.AnchorError).message).to.be.an('ProviderDoesNotExist');
-        // }
+        try {
+            await program.methods.providerRemove().accountsStrict({
+                provider: providerAccount1,
+                authority: provider.wallet.publicKey,
+                // systemProgram: SystemProgram.programId,
+            }).rpc();
+            assert.fail("The provider should not have been removed");
+        } catch (error) {
+            console.log("error: ", error?.error);
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
     });
 
 });
@@ -193,7 +276,7 @@ describe("market_v1 - remove provider", () => {
 describe("market_v1 - update provider", () => {
     let providerAccount: PublicKey;
     let program: Program<MarketV>;
-    let authority: PublicKey;
+    let authority: Keypair;
 
     before(async () => {
         // Configure the client to use the local cluster.
@@ -202,29 +285,38 @@ describe("market_v1 - update provider", () => {
 
         // add test to initialize the MarketV program
         program = anchor.workspace.MarketV as Program<MarketV>;
-        authority = provider.wallet.publicKey;
+        authority = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(authority.publicKey, 10 ** 9)
+        );
         [providerAccount,] = PublicKey.findProgramAddressSync(
-            [Buffer.from("provider"), authority.toBuffer()],
+            [Buffer.from("provider"), authority.publicKey.toBuffer()],
             program.programId
         );
         console.log("providerAccount", providerAccount.toBase58());
 
         let cp = "https://example.com/";
         await program.methods.providerAdd(
-            cp
-        ).accountsStrict({
-            provider: providerAccount,
-            authority,
-            systemProgram: SystemProgram.programId,
-        }).rpc();
+            cp,
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, authority.publicKey)
+        ).signers([authority]).rpc();
     });
 
     it("can update cp", async () => {
         let newCp = "https://new-example.com/";
         await program.methods.providerUpdateWithCp(newCp)
             .accounts({
-                authority
+                provider: providerAccount,
+                profile: PublicKey.findProgramAddressSync(
+                    [Buffer.from("provider_profile"), providerAccount.toBuffer()],
+                    program.programId
+                )[0],
+                authority: authority.publicKey,
+                operator: null,
             })
+            .signers([authority])
             .rpc();
 
         const provider = await program.account.provider.fetch(providerAccount);
@@ -237,8 +329,11 @@ describe("market_v1 - job open", () => {
     let providerAccount: PublicKey;
     let program: Program<MarketV>;
     let authority: PublicKey;
+    let marketAccount: PublicKey;
     let tokenMint: PublicKey;
+    let creditMint: PublicKey;
     let authorityTokenAccount: PublicKey;
+    let authorityCreditTokenAccount: PublicKey;
 
     before(async () => {
         // Configure the client to use the local cluster.
@@ -249,22 +344,28 @@ describe("market_v1 - job open", () => {
         program = anchor.workspace.MarketV as Program<MarketV>;
         authority = provider.wallet.publicKey;
 
-        // Create a mock USDC mint
-        tokenMint = await createMint(
-            provider.connection,
-            (provider.wallet as anchor.Wallet).payer,
-            authority,
-            null,
-            6 // USDC typically has 6 decimal places
+        // the market is initialized once
+        [marketAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("market")],
+            program.programId
         );
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
 
         // get token account of the owner
-        authorityTokenAccount = await createAssociatedTokenAccount(
+        authorityTokenAccount = (await getOrCreateAssociatedTokenAccount(
             provider.connection,
             (provider.wallet as anchor.Wallet).payer,
             tokenMint,
             authority
-        );
+        )).address;
+        authorityCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(
+            provider.connection,
+            (provider.wallet as anchor.Wallet).payer,
+            creditMint,
+            authority
+        )).address;
 
         // mint tokens to owner
         await mintTo(
@@ -276,44 +377,24 @@ describe("market_v1 - job open", () => {
             10 ** 8 // Amount of tokens to mint (in smallest unit, e.g., 1 USDC = 1,000,000 micro USDC)
         );
 
-        const RATE_LOCK_SELECTOR = "RATE_LOCK";
-
-        // initialize the MarketV program
-        let waitTime = new anchor.BN(60);
-        await program.methods.initialize(
-            RATE_LOCK_SELECTOR,
-            waitTime,
-            authority,
-        ).accounts({
-            admin: authority,
-            tokenMint,
-        }).rpc();
-
+        // the wallet provider is registered by the add provider tests
         [providerAccount,] = PublicKey.findProgramAddressSync(
             [Buffer.from("provider"), authority.toBuffer()],
             program.programId
         );
-
-        // add provider
-        let cp = "https://example.com/";
-        await program.methods.providerAdd(
-            cp
-        ).accounts({
-            authority,
-        }).rpc();
     });
 
     it("can open job", async () => {
         const ownerTokenAccountDataInitial = await program.provider.connection.getTokenAccountBalance(authorityTokenAccount);
 
-        let jobIndex = new anchor.BN(1);
+        let jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
         let jobAccount: PublicKey;
         [jobAccount,] = PublicKey.findProgramAddressSync(
-            [Buffer.from("job"), Buffer.from(jobIndex.toArray('le', 8))],
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
             program.programId
         );
         let metadata = "metadata example",
-            provider = providerAccount,
+            provider = authority,
             rate = new anchor.BN(10),
             balance = new anchor.BN(100);
 
@@ -322,10 +403,29 @@ describe("market_v1 - job open", () => {
             provider,
             rate,
             balance
-        ).accounts({
+        ).accountsStrict({
+            market: marketAccount,
             job: jobAccount,
+            owner: authority,
             tokenMint,
-            ownerTokenAccount: authorityTokenAccount,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: authorityTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: authorityCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
         }).rpc();
 
         const jobData = await program.account.job.fetch(jobAccount);
@@ -341,58 +441,51 @@ describe("market_v1 - job open", () => {
         expect(new anchor.BN(ownerTokenAccountDataInitial.value.amount)
                 .sub(new anchor.BN(ownerTokenAccountDataFinal.value.amount)).toString())
             .to.eq(balance.toString());
-
-        // check mint token balance of job token account
-        let jobTokenAddress: PublicKey;
-        [jobTokenAddress,] = PublicKey.findProgramAddressSync(
-            [Buffer.from("job_token"), tokenMint.toBuffer()],
-            program.programId
-        );
-        const jobTokenAccountData = await program.provider.connection.getTokenAccountBalance(jobTokenAddress);
-        expect(jobTokenAccountData.value.amount).to.eq(balance.toString());
     });
 });
 
-describe.only("market_v1 - job settle", () => {
+describe("market_v1 - job settle", () => {
     let provider: anchor.AnchorProvider;
-    // let provider: BankrunProvider;
     let providerAccount: PublicKey;
+    let providerWallet: Keypair;
     let program: Program<MarketV>;
     let authority: PublicKey;
+    let marketAccount: PublicKey;
     let tokenMint: PublicKey;
+    let creditMint: PublicKey;
     let providerTokenAccount: PublicKey;
+    let jobIndex: anchor.BN;
 
     before(async () => {
         // Configure the client to use the local cluster.
         provider = anchor.AnchorProvider.env();
-        // const context = await startAnchor(".", [], []);
-        // provider = new BankrunProvider(context);
         anchor.setProvider(provider);
 
-        console.log("HERE1");
         // add test to initialize the MarketV program
         program = anchor.workspace.MarketV as Program<MarketV>;
-        console.log("HERE2");
         authority = provider.wallet.publicKey;
-        console.log("HERE3");
 
-        // Create a mock USDC mint
-        tokenMint = await createMint(
-            provider.connection,
-            (provider.wallet as anchor.Wallet).payer,
-            authority,
-            null,
-            6 // USDC typically has 6 decimal places
+        [marketAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("market")],
+            program.programId
         );
-        console.log("HERE4");
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
 
         // get token account of the owner
-        let authorityTokenAccount = await createAssociatedTokenAccount(
+        let authorityTokenAccount = (await getOrCreateAssociatedTokenAccount(
             provider.connection,
             (provider.wallet as anchor.Wallet).payer,
             tokenMint,
             authority
-        );
+        )).address;
+        let authorityCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(
+            provider.connection,
+            (provider.wallet as anchor.Wallet).payer,
+            creditMint,
+            authority
+        )).address;
 
         // mint tokens to owner
         await mintTo(
@@ -404,155 +497,156 @@ describe.only("market_v1 - job settle", () => {
             10 ** 8 // Amount of tokens to mint (in smallest unit, e.g., 1 USDC = 1,000,000 micro USDC)
         );
 
-        const RATE_LOCK_SELECTOR = "RATE_LOCK";
-
-        // initialize the MarketV program
-        let waitTime = new anchor.BN(60);
-        await program.methods.initialize(
-            RATE_LOCK_SELECTOR,
-            waitTime,
-            authority,
-        ).accounts({
-            admin: authority,
-            tokenMint,
-        }).rpc();
-
-        // [providerAccount,] = PublicKey.findProgramAddressSync(
-        //     [Buffer.from("provider"), authority.toBuffer()],
-        //     program.programId
-        // );
         // generate random wallet for provider
-        const providerWallet = Keypair.generate();
-        providerAccount = providerWallet.publicKey;
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
         // get token account of the provider
         providerTokenAccount = await createAssociatedTokenAccount(
             provider.connection,
             (provider.wallet as anchor.Wallet).payer,
             tokenMint,
-            providerAccount
+            providerWallet.publicKey
         );
-        console.log("HERE5");
 
         // add provider
         let cp = "https://example.com/";
         await program.methods.providerAdd(
-            cp
-        ).accounts({
-            authority,
-        }).rpc();
+            cp,
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
 
-        let jobIndex = new anchor.BN(1);
+        jobIndex = marketData.jobIndex;
         let jobAccount: PublicKey;
         [jobAccount,] = PublicKey.findProgramAddressSync(
-            [Buffer.from("job"), Buffer.from(jobIndex.toArray('le', 8))],
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
             program.programId
         );
         let metadata = "metadata example",
             rate = new anchor.BN(5 * 10 ** 12),
-            balance = new anchor.BN(100);
+            balance = new anchor.BN(1000);
 
         await program.methods.jobOpen(
             metadata,
-            providerAccount,
+            providerWallet.publicKey,
             rate,
             balance
-        ).accounts({
+        ).accountsStrict({
+            market: marketAccount,
             job: jobAccount,
+            owner: authority,
             tokenMint,
-            ownerTokenAccount: authorityTokenAccount,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: authorityTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: authorityCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
         }).rpc();
-        console.log("HERE6");
+
+        // billing starts once the provider accepts the job
+        await program.methods.jobAccept(jobIndex).accountsStrict({
+            market: marketAccount,
+            job: jobAccount,
+            providerAccount,
+            operator: null,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
     });
 
     it("can settle job", async () => {
-        // const ownerTokenAccountDataInitial = await program.provider.connection.getTokenAccountBalance(authorityTokenAccount);
-
-        // Log the current blockchain time
-        let slot = await program.provider.connection.getSlot();
-        let blockTime = await program.provider.connection.getBlockTime(slot);
-        console.log("Current blockchain time1:", slot, new Date(blockTime * 1000).toISOString());
-        // console.log("time: ", await provider.connection.getLatestBlockhashAndContext());
-
-        // const context = await startAnchor(".", [], []);
-        // let bankrunProvider = new BankrunProvider(context);
-        // anchor.setProvider(bankrunProvider);
-        // await bankrunProvider.context.warpToEpoch(BigInt(20000));
-        console.log("ProgramId: ", program.programId );
-        const context = await start(
-            [{ name: "market_v", programId: program.programId }],
-            [],
-        );
-        const currentClock = await context.banksClient.getClock();
-        context.setClock(
-            new Clock(
-                currentClock.slot,
-                currentClock.epochStartTimestamp,
-                currentClock.epoch,
-                currentClock.leaderScheduleEpoch,
-                BigInt(50),
-            ),
-        )
-
-        slot = await program.provider.connection.getSlot();
-        blockTime = await program.provider.connection.getBlockTime(slot);
-        console.log("Current blockchain time2:", slot, new Date(blockTime * 1000).toISOString());
-
-
-        console.log("DONE");
-        
         // settle job
-        let jobIndex = new anchor.BN(1);
         let jobAccount: PublicKey;
         [jobAccount,] = PublicKey.findProgramAddressSync(
-            [Buffer.from("job"), Buffer.from(jobIndex.toArray('le', 8))],
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
             program.programId
         );
+        const jobDataInitial = await program.account.job.fetch(jobAccount);
+
         // execute the settle job
-        let txn = await program.methods.jobSettle(jobIndex)
-            .accounts({
+        await program.methods.jobSettle(jobIndex)
+            .accountsStrict({
+                market: marketAccount,
+                job: jobAccount,
                 tokenMint,
+                programTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("job_token"), tokenMint.toBuffer()],
+                    program.programId
+                )[0],
+                providerAccount,
                 providerTokenAccount,
+                creditMint,
+                programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("credit_token"), creditMint.toBuffer()],
+                    program.programId
+                )[0],
+                owner: authority,
+                state: PublicKey.findProgramAddressSync(
+                    [Buffer.from("state")],
+                    anchor.workspace.OysterCredits.programId
+                )[0],
+                creditProgramUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                    anchor.workspace.OysterCredits.programId
+                )[0],
+                creditProgramReserve: PublicKey.findProgramAddressSync(
+                    [Buffer.from("reserve"), tokenMint.toBuffer()],
+                    anchor.workspace.OysterCredits.programId
+                )[0],
+                creditProgramProviderTier: PublicKey.findProgramAddressSync(
+                    [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                    anchor.workspace.OysterCredits.programId
+                )[0],
+                creditProgram: anchor.workspace.OysterCredits.programId,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
             }).rpc({commitment: 'confirmed'});
 
-        // get txn timestamp
-        let txnReceipt = await program.provider.connection.getParsedTransaction(txn, {commitment: 'confirmed'});
-        let txnTimestamp = new anchor.BN(txnReceipt.blockTime);
-
         const jobData = await program.account.job.fetch(jobAccount);
-        let amount = (jobData.rate.mul(txnTimestamp
-                                .sub(jobData.lastSettled)
-                                .add(new anchor.BN(10 ** 12 - 1))))
+        let amount = (jobData.rate.mul(jobData.lastSettled
+                                .sub(jobDataInitial.lastSettled))
+                                .add(new anchor.BN(10 ** 12 - 1)))
                                 .div(new anchor.BN(10 ** 12));
 
         expect(jobData.index.eq(jobIndex)).to.be.true;
-        // expect(jobData.provider.toBase58()).to.equal(provider.toBase58());
-        // expect(jobData.rate.eq(rate)).to.be.true;
-        console.log("data: ", jobData.balance.toNumber(), new anchor.BN(100).sub(amount).toNumber());
-        // expect(jobData.balance).to.eq(new anchor.BN(100).sub(amount));
-
-        // // check mint token balance of owner
-        // const ownerTokenAccountDataFinal = await program.provider.connection.getTokenAccountBalance(authorityTokenAccount);
-        // expect(new anchor.BN(ownerTokenAccountDataInitial.value.amount)
-        //         .sub(new anchor.BN(ownerTokenAccountDataFinal.value.amount)).toString())
-        //     .to.eq(balance.toString());
-
-        // // check mint token balance of job token account
-        // let jobTokenAddress: PublicKey;
-        // [jobTokenAddress,] = PublicKey.findProgramAddressSync(
-        //     [Buffer.from("job_token"), tokenMint.toBuffer()],
-        //     program.programId
-        // );
-        // const jobTokenAccountData = await program.provider.connection.getTokenAccountBalance(jobTokenAddress);
-        // expect(jobTokenAccountData.value.amount).to.eq(balance.toString());
+        expect(jobData.balance.toString()).to.eq(new anchor.BN(1000).sub(amount).toString());
+
+        // check mint token balance of the provider
+        const providerTokenAccountData = await program.provider.connection.getTokenAccountBalance(providerTokenAccount);
+        expect(providerTokenAccountData.value.amount).to.eq(amount.toString());
     });
 });
 
 describe("market_v1 - job close", () => {
     let providerAccount: PublicKey;
+    let providerWallet: Keypair;
     let program: Program<MarketV>;
     let authority: PublicKey;
+    let marketAccount: PublicKey;
     let tokenMint: PublicKey;
+    let creditMint: PublicKey;
     let authorityTokenAccount: PublicKey;
+    let providerTokenAccount: PublicKey;
+    let jobIndex: anchor.BN;
 
     before(async () => {
         // Configure the client to use the local cluster.
@@ -563,22 +657,27 @@ describe("market_v1 - job close", () => {
         program = anchor.workspace.MarketV as Program<MarketV>;
         authority = provider.wallet.publicKey;
 
-        // Create a mock USDC mint
-        tokenMint = await createMint(
-            provider.connection,
-            (provider.wallet as anchor.Wallet).payer,
-            authority,
-            null,
-            6 // USDC typically has 6 decimal places
+        [marketAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("market")],
+            program.programId
         );
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
 
         // get token account of the owner
-        authorityTokenAccount = await createAssociatedTokenAccount(
+        authorityTokenAccount = (await getOrCreateAssociatedTokenAccount(
             provider.connection,
             (provider.wallet as anchor.Wallet).payer,
             tokenMint,
             authority
-        );
+        )).address;
+        let authorityCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(
+            provider.connection,
+            (provider.wallet as anchor.Wallet).payer,
+            creditMint,
+            authority
+        )).address;
 
         // mint tokens to owner
         await mintTo(
@@ -590,100 +689,5014 @@ describe("market_v1 - job close", () => {
             10 ** 8 // Amount of tokens to mint (in smallest unit, e.g., 1 USDC = 1,000,000 micro USDC)
         );
 
-        const RATE_LOCK_SELECTOR = "RATE_LOCK";
-
-        // initialize the MarketV program
-        let waitTime = new anchor.BN(60);
-        await program.methods.initialize(
-            RATE_LOCK_SELECTOR,
-            waitTime,
-            authority,
-        ).accounts({
-            admin: authority,
-            tokenMint,
-        }).rpc();
-
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
         [providerAccount,] = PublicKey.findProgramAddressSync(
-            [Buffer.from("provider"), authority.toBuffer()],
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
             program.programId
         );
+        providerTokenAccount = await createAssociatedTokenAccount(
+            provider.connection,
+            (provider.wallet as anchor.Wallet).payer,
+            tokenMint,
+            providerWallet.publicKey
+        );
 
         // add provider
         let cp = "https://example.com/";
         await program.methods.providerAdd(
-            cp
-        ).accounts({
-            authority,
-        }).rpc();
+            cp,
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
 
-        let jobIndex = new anchor.BN(1);
+        jobIndex = marketData.jobIndex;
         let jobAccount: PublicKey;
         [jobAccount,] = PublicKey.findProgramAddressSync(
-            [Buffer.from("job"), Buffer.from(jobIndex.toArray('le', 8))],
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
             program.programId
         );
         let metadata = "metadata example",
             rate = new anchor.BN(5 * 10 ** 12),
-            balance = new anchor.BN(100);
+            balance = new anchor.BN(1000);
 
         await program.methods.jobOpen(
             metadata,
-            providerAccount,
+            providerWallet.publicKey,
             rate,
             balance
-        ).accounts({
+        ).accountsStrict({
+            market: marketAccount,
             job: jobAccount,
+            owner: authority,
             tokenMint,
-            ownerTokenAccount: authorityTokenAccount,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: authorityTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: authorityCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
         }).rpc();
+
+        // billing starts once the provider accepts the job
+        await program.methods.jobAccept(jobIndex).accountsStrict({
+            market: marketAccount,
+            job: jobAccount,
+            providerAccount,
+            operator: null,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
     });
 
     it("can close job", async () => {
-        const ownerTokenAccountDataInitial = await program.provider.connection.getTokenAccountBalance(authorityTokenAccount);
-
         // settle job
-        let jobIndex = new anchor.BN(1);
         let jobAccount: PublicKey;
         [jobAccount,] = PublicKey.findProgramAddressSync(
-            [Buffer.from("job"), Buffer.from(jobIndex.toArray('le', 8))],
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
             program.programId
         );
+        const jobDataInitial = await program.account.job.fetch(jobAccount);
+
         // execute the settle job
-        let txn = await program.methods.jobSettle(jobIndex)
-            .accounts({
+        await program.methods.jobSettle(jobIndex)
+            .accountsStrict({
+                market: marketAccount,
+                job: jobAccount,
                 tokenMint,
-                providerTokenAccount: authorityTokenAccount,
+                programTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("job_token"), tokenMint.toBuffer()],
+                    program.programId
+                )[0],
+                providerAccount,
+                providerTokenAccount,
+                creditMint,
+                programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("credit_token"), creditMint.toBuffer()],
+                    program.programId
+                )[0],
+                owner: authority,
+                state: PublicKey.findProgramAddressSync(
+                    [Buffer.from("state")],
+                    anchor.workspace.OysterCredits.programId
+                )[0],
+                creditProgramUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                    anchor.workspace.OysterCredits.programId
+                )[0],
+                creditProgramReserve: PublicKey.findProgramAddressSync(
+                    [Buffer.from("reserve"), tokenMint.toBuffer()],
+                    anchor.workspace.OysterCredits.programId
+                )[0],
+                creditProgramProviderTier: PublicKey.findProgramAddressSync(
+                    [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                    anchor.workspace.OysterCredits.programId
+                )[0],
+                creditProgram: anchor.workspace.OysterCredits.programId,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
             }).rpc({commitment: 'confirmed'});
 
-        // get txn timestamp
-        let txnReceipt = await program.provider.connection.getParsedTransaction(txn, {commitment: 'confirmed'});
-        let txnTimestamp = new anchor.BN(txnReceipt.blockTime);
-
         const jobData = await program.account.job.fetch(jobAccount);
-        let amount = (jobData.rate.mul(txnTimestamp
-                                .sub(jobData.lastSettled)
-                                .add(new anchor.BN(10 ** 12 - 1))))
+        let amount = (jobData.rate.mul(jobData.lastSettled
+                                .sub(jobDataInitial.lastSettled))
+                                .add(new anchor.BN(10 ** 12 - 1)))
                                 .div(new anchor.BN(10 ** 12));
 
         expect(jobData.index.eq(jobIndex)).to.be.true;
-        // expect(jobData.provider.toBase58()).to.equal(provider.toBase58());
-        // expect(jobData.rate.eq(rate)).to.be.true;
-        console.log("data: ", jobData.balance.toNumber(), new anchor.BN(100).sub(amount).toNumber());
-        // expect(jobData.balance).to.eq(new anchor.BN(100).sub(amount));
-
-        // // check mint token balance of owner
-        // const ownerTokenAccountDataFinal = await program.provider.connection.getTokenAccountBalance(authorityTokenAccount);
-        // expect(new anchor.BN(ownerTokenAccountDataInitial.value.amount)
-        //         .sub(new anchor.BN(ownerTokenAccountDataFinal.value.amount)).toString())
-        //     .to.eq(balance.toString());
-
-        // // check mint token balance of job token account
-        // let jobTokenAddress: PublicKey;
-        // [jobTokenAddress,] = PublicKey.findProgramAddressSync(
-        //     [Buffer.from("job_token"), tokenMint.toBuffer()],
-        //     program.programId
-        // );
-        // const jobTokenAccountData = await program.provider.connection.getTokenAccountBalance(jobTokenAddress);
-        // expect(jobTokenAccountData.value.amount).to.eq(balance.toString());
-    });
-});
\ No newline at end of file
+        expect(jobData.balance.toString()).to.eq(new anchor.BN(1000).sub(amount).toString());
+    });
+});
+
+describe("market_v1 - credit refund policy", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let creditProgram: Program<OysterCredits>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerTokenAccount: PublicKey;
+    let owner: Keypair;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+
+    // Open a job funded with `credits` credits on top of `tokens` tokens
+    async function openJob(tokens: number, credits: number): Promise<any> {
+        await creditProgram.methods.mint(new anchor.BN(credits)).accountsStrict({
+            state: PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId)[0],
+            minterRole: PublicKey.findProgramAddressSync(
+                [Buffer.from("minter"), payer.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditMint,
+            tokenAccount: ownerCreditTokenAccount,
+            signer: payer.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        }).rpc();
+        // credits are only pulled up to the approved amount
+        await approve(provider.connection, payer, ownerCreditTokenAccount, payer.publicKey, owner, credits);
+
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(10),
+            new anchor.BN(tokens + credits)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: owner.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+                program.programId
+            )[0],
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).signers([owner]).rpc();
+
+        return { jobIndex, job };
+    }
+
+    async function withdraw(job: any, amount: number, issuerCreditTokenAccount: PublicKey | null) {
+        await program.methods.jobWithdraw(job.jobIndex, new anchor.BN(amount)).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            owner: owner.publicKey,
+            jobNftAccount: null,
+            tokenMint,
+            providerAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+                program.programId
+            )[0],
+            providerTokenAccount,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            issuerCreditTokenAccount,
+            state: PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId)[0],
+            creditProgramUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramReserve: PublicKey.findProgramAddressSync(
+                [Buffer.from("reserve"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramProviderTier: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgram: creditProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).signers([owner]).rpc();
+    }
+
+    async function setRefundPolicy(policy: any, issuer: PublicKey) {
+        await program.methods.updateCreditRefundPolicy(policy, issuer).accountsStrict({
+            market: marketAccount,
+            admin: payer.publicKey,
+        }).rpc();
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        creditProgram = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        providerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, providerWallet.publicKey);
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(
+                program,
+                PublicKey.findProgramAddressSync(
+                    [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+                    program.programId
+                )[0],
+                providerWallet.publicKey
+            )
+        ).signers([providerWallet]).rpc();
+
+        owner = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(owner.publicKey, 10 ** 9)
+        );
+        ownerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, owner.publicKey);
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, creditMint, owner.publicKey);
+
+        // the wallet mints the credits used by the jobs
+        await creditProgram.methods.addMinter(
+            payer.publicKey,
+            new anchor.BN(10 ** 9),
+            new anchor.BN(86400),
+            new anchor.BN(10 ** 12)
+        ).accountsStrict({
+            state: PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId)[0],
+            minterRole: PublicKey.findProgramAddressSync(
+                [Buffer.from("minter"), payer.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            authority: payer.publicKey,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+    });
+
+    it("refunds unused credits to the owner by default", async () => {
+        const job = await openJob(40, 60);
+        let jobData = await program.account.job.fetch(job.job);
+        expect(jobData.balance.toNumber()).to.equal(100);
+        expect(jobData.creditBalance.toNumber()).to.equal(60);
+
+        const ownerTokensBefore = (await getAccount(provider.connection, ownerTokenAccount)).amount;
+        await withdraw(job, 100, null);
+
+        jobData = await program.account.job.fetch(job.job);
+        expect(jobData.balance.toNumber()).to.equal(0);
+        expect(jobData.creditBalance.toNumber()).to.equal(0);
+        const ownerTokensAfter = (await getAccount(provider.connection, ownerTokenAccount)).amount;
+        expect(new anchor.BN(ownerTokensAfter.toString())
+                .sub(new anchor.BN(ownerTokensBefore.toString())).toString())
+            .to.eq("40");
+        expect((await getAccount(provider.connection, ownerCreditTokenAccount)).amount.toString()).to.equal("60");
+    });
+
+    it("returns unused credits to the issuer", async () => {
+        const issuer = Keypair.generate();
+        const issuerCreditTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, creditMint, issuer.publicKey);
+        await setRefundPolicy({ issuer: {} }, issuer.publicKey);
+
+        const job = await openJob(0, 50);
+        const ownerCreditsBefore = (await getAccount(provider.connection, ownerCreditTokenAccount)).amount;
+
+        // the issuer account has to be passed along
+        try {
+            await withdraw(job, 50, null);
+            assert.fail("withdrawing without the issuer account should fail");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidCreditIssuer");
+        }
+
+        await withdraw(job, 50, issuerCreditTokenAccount);
+
+        expect((await getAccount(provider.connection, issuerCreditTokenAccount)).amount.toString()).to.equal("50");
+        expect((await getAccount(provider.connection, ownerCreditTokenAccount)).amount.toString())
+            .to.equal(ownerCreditsBefore.toString());
+
+        await setRefundPolicy({ user: {} }, PublicKey.default);
+    });
+
+    it("burns unused credits", async () => {
+        await setRefundPolicy({ burn: {} }, PublicKey.default);
+
+        const job = await openJob(0, 50);
+        const supplyBefore = (await getMint(provider.connection, creditMint)).supply;
+        const ownerCreditsBefore = (await getAccount(provider.connection, ownerCreditTokenAccount)).amount;
+
+        await withdraw(job, 50, null);
+
+        const supplyAfter = (await getMint(provider.connection, creditMint)).supply;
+        expect(new anchor.BN(supplyBefore.toString())
+                .sub(new anchor.BN(supplyAfter.toString())).toString())
+            .to.eq("50");
+        expect((await getAccount(provider.connection, ownerCreditTokenAccount)).amount.toString())
+            .to.equal(ownerCreditsBefore.toString());
+
+        await setRefundPolicy({ user: {} }, PublicKey.default);
+    });
+
+    it("cannot return credits to an unset issuer", async () => {
+        try {
+            await setRefundPolicy({ issuer: {} }, PublicKey.default);
+            assert.fail("the refund policy should not have been updated");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidCreditIssuer");
+        }
+    });
+});
+
+describe("market_v1 - credit redemption haircut", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let creditProgram: Program<OysterCredits>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let state: PublicKey;
+    let reserve: PublicKey;
+    let payerTokenAccount: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let providerTokenAccount: PublicKey;
+    let owner: Keypair;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        creditProgram = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+        [state,] = PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId);
+
+        // credits are redeemed from the reserve of the market token
+        [reserve,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("reserve"), tokenMint.toBuffer()],
+            creditProgram.programId
+        );
+        const programUsdcTokenAccount = PublicKey.findProgramAddressSync(
+            [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+            creditProgram.programId
+        )[0];
+        if (!(await creditProgram.account.reserve.fetchNullable(reserve))) {
+            await creditProgram.methods.addReserve().accountsStrict({
+                state,
+                usdcMint: tokenMint,
+                creditMint,
+                reserve,
+                programUsdcTokenAccount,
+                authority: payer.publicKey,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            }).rpc();
+        }
+        payerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, payerTokenAccount, payer, 10 ** 6);
+        await creditProgram.methods.depositReserve(new anchor.BN(10 ** 6)).accountsStrict({
+            state,
+            usdcMint: tokenMint,
+            reserve,
+            programUsdcTokenAccount,
+            depositorTokenAccount: payerTokenAccount,
+            depositor: payer.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        }).rpc();
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        providerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, providerWallet.publicKey);
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+
+        // the provider redeems at 80% of face value
+        await creditProgram.methods.setTierRedemptionRate(1, 8000).accountsStrict({
+            state,
+            authority: payer.publicKey,
+        }).rpc();
+        await creditProgram.methods.setProviderTier(providerWallet.publicKey, 1).accountsStrict({
+            state,
+            providerTier: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            authority: payer.publicKey,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+
+        owner = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(owner.publicKey, 10 ** 9)
+        );
+        ownerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, owner.publicKey);
+        ownerCreditTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, creditMint, owner.publicKey);
+        await creditProgram.methods.addMinter(
+            payer.publicKey,
+            new anchor.BN(10 ** 9),
+            new anchor.BN(86400),
+            new anchor.BN(10 ** 12)
+        ).accountsStrict({
+            state,
+            minterRole: PublicKey.findProgramAddressSync(
+                [Buffer.from("minter"), payer.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            authority: payer.publicKey,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        await creditProgram.methods.mint(new anchor.BN(1000)).accountsStrict({
+            state,
+            minterRole: PublicKey.findProgramAddressSync(
+                [Buffer.from("minter"), payer.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditMint,
+            tokenAccount: ownerCreditTokenAccount,
+            signer: payer.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        }).rpc();
+        await approve(provider.connection, payer, ownerCreditTokenAccount, payer.publicKey, owner, 1000);
+    });
+
+    after(async () => {
+        await creditProgram.methods.setTierRedemptionRate(1, 10000).accountsStrict({
+            state,
+            authority: payer.publicKey,
+        }).rpc();
+        await creditProgram.methods.setHaircutToSubsidy(false).accountsStrict({
+            state,
+            authority: payer.publicKey,
+        }).rpc();
+    });
+
+    it("pays credit settlements at the provider tier rate and keeps the haircut", async () => {
+        await creditProgram.methods.setHaircutToSubsidy(true).accountsStrict({
+            state,
+            authority: payer.publicKey,
+        }).rpc();
+
+        // a job funded only with credits
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(5 * 10 ** 12),
+            new anchor.BN(1000)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: owner.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).signers([owner]).rpc();
+        expect((await program.account.job.fetch(job)).creditBalance.toNumber()).to.equal(1000);
+
+        await program.methods.jobAccept(jobIndex).accountsStrict({
+            market: marketAccount,
+            job,
+            providerAccount,
+            operator: null,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+        const jobDataInitial = await program.account.job.fetch(job);
+        const reserveDataInitial = await creditProgram.account.reserve.fetch(reserve);
+        await new Promise((resolve) => setTimeout(resolve, 2000));
+
+        await program.methods.jobSettle(jobIndex).accountsStrict({
+            market: marketAccount,
+            job,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            providerAccount,
+            providerTokenAccount,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            owner: payer.publicKey,
+            state,
+            creditProgramUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramReserve: reserve,
+            creditProgramProviderTier: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgram: creditProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+
+        const jobData = await program.account.job.fetch(job);
+        const settled = jobData.rate.mul(jobData.lastSettled.sub(jobDataInitial.lastSettled))
+            .add(new anchor.BN(10 ** 12 - 1))
+            .div(new anchor.BN(10 ** 12));
+        const paid = settled.muln(8000).divn(10000);
+        expect(settled.toNumber()).to.be.greaterThan(0);
+        expect(jobData.creditBalance.toString()).to.equal(new anchor.BN(1000).sub(settled).toString());
+        expect((await getAccount(provider.connection, providerTokenAccount)).amount.toString()).to.equal(paid.toString());
+
+        const reserveData = await creditProgram.account.reserve.fetch(reserve);
+        expect(reserveData.totalRedeemed.sub(reserveDataInitial.totalRedeemed).toString()).to.equal(settled.toString());
+        expect(reserveData.totalPaid.sub(reserveDataInitial.totalPaid).toString()).to.equal(paid.toString());
+        expect(reserveData.subsidyPool.sub(reserveDataInitial.subsidyPool).toString())
+            .to.equal(settled.sub(paid).toString());
+    });
+
+    it("can withdraw the subsidy pool", async () => {
+        const subsidyPool = (await creditProgram.account.reserve.fetch(reserve)).subsidyPool;
+        const accounts = {
+            state,
+            usdcMint: tokenMint,
+            reserve,
+            programUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            receiver: payerTokenAccount,
+            authority: payer.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        };
+
+        try {
+            await creditProgram.methods.withdrawSubsidy(subsidyPool.addn(1)).accountsStrict(accounts).rpc();
+            assert.fail("the subsidy pool should not have been overdrawn");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("NotEnoughSubsidy");
+        }
+
+        const receiverBefore = (await getAccount(provider.connection, payerTokenAccount)).amount;
+        await creditProgram.methods.withdrawSubsidy(subsidyPool).accountsStrict(accounts).rpc();
+
+        expect((await creditProgram.account.reserve.fetch(reserve)).subsidyPool.toNumber()).to.equal(0);
+        const receiverAfter = (await getAccount(provider.connection, payerTokenAccount)).amount;
+        expect(new anchor.BN(receiverAfter.toString())
+                .sub(new anchor.BN(receiverBefore.toString())).toString())
+            .to.eq(subsidyPool.toString());
+    });
+});
+
+describe("market_v1 - job provider checks", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+
+    async function openJob(providerAuthority: PublicKey): Promise<PublicKey> {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerAuthority,
+            new anchor.BN(10),
+            new anchor.BN(100)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider"), providerAuthority.toBuffer()],
+                program.programId
+            )[0],
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        return job;
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods.providerAdd(
+            "https://provider.example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+    });
+
+    it("records the provider control plane on open", async () => {
+        const job = await openJob(providerWallet.publicKey);
+
+        const jobData = await program.account.job.fetch(job);
+        expect(jobData.provider.toBase58()).to.equal(providerWallet.publicKey.toBase58());
+        expect(jobData.providerCp).to.equal("https://provider.example.com/");
+    });
+
+    it("cannot open a job for an unregistered provider", async () => {
+        try {
+            await openJob(Keypair.generate().publicKey);
+            assert.fail("the job should not have been opened");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("AccountNotInitialized");
+        }
+    });
+});
+
+describe("market_v1 - provider active jobs", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let providerTokenAccount: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+    const jobs: any[] = [];
+
+    async function openJob() {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(10),
+            new anchor.BN(100)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        jobs.push({ jobIndex, job });
+    }
+
+    async function closeProvider() {
+        await program.methods.providerClose().accountsStrict({
+            provider: providerAccount,
+            profile: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider_profile"), providerAccount.toBuffer()],
+                program.programId
+            )[0],
+            rateCard: PublicKey.findProgramAddressSync(
+                [Buffer.from("rate_card"), providerAccount.toBuffer()],
+                program.programId
+            )[0],
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        providerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, providerWallet.publicKey);
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+    });
+
+    it("counts the active jobs of a provider", async () => {
+        await openJob();
+        await openJob();
+
+        expect((await program.account.provider.fetch(providerAccount)).activeJobs.toNumber()).to.equal(2);
+    });
+
+    it("winds a provider with active jobs down instead of closing it", async () => {
+        await program.methods.providerRemove().accountsStrict({
+            provider: providerAccount,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+
+        const providerData = await program.account.provider.fetch(providerAccount);
+        expect(providerData.status).to.have.property("windingDown");
+        expect(providerData.activeJobs.toNumber()).to.equal(2);
+
+        try {
+            await closeProvider();
+            assert.fail("the provider should not have been closed");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("ProviderHasActiveJobs");
+        }
+
+        try {
+            await program.methods.providerRemove().accountsStrict({
+                provider: providerAccount,
+                authority: providerWallet.publicKey,
+            }).signers([providerWallet]).rpc();
+            assert.fail("the provider is already winding down");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("ProviderNotActive");
+        }
+    });
+
+    it("cannot open a job for a provider winding down", async () => {
+        try {
+            await openJob();
+            assert.fail("the job should not have been opened");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("ProviderNotActive");
+        }
+    });
+
+    it("can close the provider once its jobs are closed", async () => {
+        for (const job of jobs) {
+            await program.methods.jobClose(job.jobIndex).accountsStrict({
+                market: marketAccount,
+                job: job.job,
+                topupPolicy: PublicKey.findProgramAddressSync(
+                    [Buffer.from("topup_policy"), job.job.toBuffer()],
+                    program.programId
+                )[0],
+                tokenMint,
+                programTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("job_token"), tokenMint.toBuffer()],
+                    program.programId
+                )[0],
+                userTokenAccount: ownerTokenAccount,
+                providerAccount,
+                jobNftMint: null,
+                jobNftAccount: null,
+                providerTokenAccount,
+                creditMint,
+                programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("credit_token"), creditMint.toBuffer()],
+                    program.programId
+                )[0],
+                userCreditTokenAccount: ownerCreditTokenAccount,
+                issuerCreditTokenAccount: null,
+                owner: payer.publicKey,
+                state: PublicKey.findProgramAddressSync(
+                    [Buffer.from("state")],
+                    anchor.workspace.OysterCredits.programId
+                )[0],
+                creditProgramUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                    anchor.workspace.OysterCredits.programId
+                )[0],
+                creditProgramReserve: PublicKey.findProgramAddressSync(
+                    [Buffer.from("reserve"), tokenMint.toBuffer()],
+                    anchor.workspace.OysterCredits.programId
+                )[0],
+                creditProgramProviderTier: PublicKey.findProgramAddressSync(
+                    [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                    anchor.workspace.OysterCredits.programId
+                )[0],
+                creditProgram: anchor.workspace.OysterCredits.programId,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            }).rpc();
+        }
+        expect((await program.account.provider.fetch(providerAccount)).activeJobs.toNumber()).to.equal(0);
+
+        await closeProvider();
+        expect(await program.account.provider.fetchNullable(providerAccount)).to.be.null;
+    });
+});
+
+describe("market_v1 - job termination by provider", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let creditProgram: Program<OysterCredits>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let providerTokenAccount: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+    let activeJob: any;
+    let pendingJob: any;
+
+    async function openJob(): Promise<any> {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(10),
+            new anchor.BN(1000)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        return { jobIndex, job };
+    }
+
+    async function terminate(job: any, authority: Keypair) {
+        await program.methods.jobTerminateByProvider(job.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            providerAccount,
+            authority: authority.publicKey,
+        }).signers([authority]).rpc();
+    }
+
+    async function finalize(job: any) {
+        await program.methods.jobTerminateFinalize(job.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            topupPolicy: PublicKey.findProgramAddressSync(
+                [Buffer.from("topup_policy"), job.job.toBuffer()],
+                program.programId
+            )[0],
+            owner: payer.publicKey,
+            jobNftMint: null,
+            jobNftAccount: null,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            providerTokenAccount,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            issuerCreditTokenAccount: null,
+            authority: providerWallet.publicKey,
+            state: PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId)[0],
+            creditProgramUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramReserve: PublicKey.findProgramAddressSync(
+                [Buffer.from("reserve"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramProviderTier: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgram: creditProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).signers([providerWallet]).rpc();
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        creditProgram = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        providerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, providerWallet.publicKey);
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+
+        activeJob = await openJob();
+        await program.methods.jobAccept(activeJob.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: activeJob.job,
+            providerAccount,
+            operator: null,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+        pendingJob = await openJob();
+    });
+
+    it("only the provider can terminate a job", async () => {
+        try {
+            await terminate(activeJob, payer);
+            assert.fail("the job should not have been terminated");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
+    });
+
+    it("cannot terminate a job that is not active", async () => {
+        try {
+            await terminate(pendingJob, providerWallet);
+            assert.fail("the job should not have been terminated");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidJobStatus");
+        }
+    });
+
+    it("can terminate a job with the notice period", async () => {
+        await terminate(activeJob, providerWallet);
+
+        const jobData = await program.account.job.fetch(activeJob.job);
+        expect(jobData.status).to.have.property("terminated");
+        expect(jobData.endAt.toNumber()).to.be.closeTo(Math.floor(Date.now() / 1000) + 60, 5);
+    });
+
+    it("cannot deposit into a terminated job", async () => {
+        try {
+            await program.methods.jobDeposit(activeJob.jobIndex, new anchor.BN(100)).accountsStrict({
+                market: marketAccount,
+                job: activeJob.job,
+                owner: payer.publicKey,
+                tokenMint,
+                ownerTokenAccount,
+                providerAccount,
+                providerTokenAccount,
+                programTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("job_token"), tokenMint.toBuffer()],
+                    program.programId
+                )[0],
+                userTokenAccount: ownerTokenAccount,
+                creditMint,
+                programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("credit_token"), creditMint.toBuffer()],
+                    program.programId
+                )[0],
+                userCreditTokenAccount: ownerCreditTokenAccount,
+                state: PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId)[0],
+                creditProgramUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                    creditProgram.programId
+                )[0],
+                creditProgramReserve: PublicKey.findProgramAddressSync(
+                    [Buffer.from("reserve"), tokenMint.toBuffer()],
+                    creditProgram.programId
+                )[0],
+                creditProgramProviderTier: PublicKey.findProgramAddressSync(
+                    [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                    creditProgram.programId
+                )[0],
+                creditProgram: creditProgram.programId,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            }).rpc();
+            assert.fail("the deposit should have been rejected");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("JobTerminating");
+        }
+    });
+
+    it("cannot finalize a job that is not terminated", async () => {
+        try {
+            await finalize(pendingJob);
+            assert.fail("the job should not have been finalized");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("JobNotTerminating");
+        }
+    });
+
+    it("cannot finalize a termination before the notice period", async () => {
+        try {
+            await finalize(activeJob);
+            assert.fail("the job should not have been finalized");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("NoticePeriodNotOver");
+        }
+    });
+
+    it("can finalize a termination after the notice period", async () => {
+        await new Promise((resolve) => setTimeout(resolve, 61000));
+        const ownerTokensBefore = (await getAccount(provider.connection, ownerTokenAccount)).amount;
+
+        await finalize(activeJob);
+
+        expect(await program.account.job.fetchNullable(activeJob.job)).to.be.null;
+        const ownerTokensAfter = (await getAccount(provider.connection, ownerTokenAccount)).amount;
+        expect(new anchor.BN(ownerTokensAfter.toString())
+            .sub(new anchor.BN(ownerTokensBefore.toString())).toNumber()).to.be.greaterThan(0);
+        expect((await program.account.provider.fetch(providerAccount)).activeJobs.toNumber()).to.equal(1);
+    });
+});
+
+describe("market_v1 - provider operators", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let operatorWallet: Keypair;
+    let operatorAccount: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+
+    async function openJob(): Promise<any> {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(10),
+            new anchor.BN(1000)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        return { jobIndex, job };
+    }
+
+    async function setOperator(permissions: number) {
+        await program.methods.providerOperatorSet(operatorWallet.publicKey, permissions).accountsStrict({
+            provider: providerAccount,
+            providerOperator: operatorAccount,
+            authority: providerWallet.publicKey,
+            systemProgram: SystemProgram.programId,
+        }).signers([providerWallet]).rpc();
+    }
+
+    async function updateCp(cp: string, operator: PublicKey | null) {
+        await program.methods.providerUpdateWithCp(cp).accountsStrict({
+            provider: providerAccount,
+            profile: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider_profile"), providerAccount.toBuffer()],
+                program.programId
+            )[0],
+            authority: operatorWallet.publicKey,
+            operator,
+            systemProgram: SystemProgram.programId,
+        }).signers([operatorWallet]).rpc();
+    }
+
+    async function accept(job: any, operator: PublicKey | null) {
+        await program.methods.jobAccept(job.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            providerAccount,
+            operator,
+            authority: operatorWallet.publicKey,
+        }).signers([operatorWallet]).rpc();
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+
+        // the hot key only pays for its own transactions
+        operatorWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(operatorWallet.publicKey, 10 ** 9)
+        );
+        [operatorAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider_operator"), providerAccount.toBuffer(), operatorWallet.publicKey.toBuffer()],
+            program.programId
+        );
+    });
+
+    it("cannot set an operator with invalid permissions", async () => {
+        for (const permissions of [0, 2, 8]) {
+            try {
+                await setOperator(permissions);
+                assert.fail("the operator should not have been set");
+            } catch (error) {
+                expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidOperatorPermissions");
+            }
+        }
+    });
+
+    it("only the provider can set an operator", async () => {
+        try {
+            await program.methods.providerOperatorSet(operatorWallet.publicKey, 1).accountsStrict({
+                provider: providerAccount,
+                providerOperator: operatorAccount,
+                authority: operatorWallet.publicKey,
+                systemProgram: SystemProgram.programId,
+            }).signers([operatorWallet]).rpc();
+            assert.fail("the operator should not have been set");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
+    });
+
+    it("an operator can update the control plane", async () => {
+        await setOperator(1);
+
+        const operatorData = await program.account.providerOperator.fetch(operatorAccount);
+        expect(operatorData.provider.toBase58()).to.equal(providerAccount.toBase58());
+        expect(operatorData.operator.toBase58()).to.equal(operatorWallet.publicKey.toBase58());
+        expect(operatorData.permissions).to.equal(1);
+
+        await updateCp("https://operator.example.com/", operatorAccount);
+        expect((await program.account.provider.fetch(providerAccount)).cp).to.equal("https://operator.example.com/");
+    });
+
+    it("cannot act for a provider without an operator account", async () => {
+        try {
+            await updateCp("https://unauthorized.example.com/", null);
+            assert.fail("the control plane should not have been updated");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
+    });
+
+    it("an operator cannot accept jobs without the permission", async () => {
+        const job = await openJob();
+        try {
+            await accept(job, operatorAccount);
+            assert.fail("the job should not have been accepted");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("MissingOperatorPermission");
+        }
+        expect((await program.account.job.fetch(job.job)).status).to.have.property("pending");
+    });
+
+    it("an operator can accept jobs with the permission", async () => {
+        // update the control plane and accept jobs
+        await setOperator(5);
+        expect((await program.account.providerOperator.fetch(operatorAccount)).permissions).to.equal(5);
+
+        const job = await openJob();
+        await accept(job, operatorAccount);
+        expect((await program.account.job.fetch(job.job)).status).to.have.property("active");
+    });
+
+    it("can remove an operator", async () => {
+        await program.methods.providerOperatorRemove().accountsStrict({
+            provider: providerAccount,
+            providerOperator: operatorAccount,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+        expect(await program.account.providerOperator.fetchNullable(operatorAccount)).to.be.null;
+
+        try {
+            await updateCp("https://removed.example.com/", operatorAccount);
+            assert.fail("the control plane should not have been updated");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("AccountNotInitialized");
+        }
+    });
+});
+
+describe("market_v1 - provider key rotation", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let creditProgram: Program<OysterCredits>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let providerTokenAccount: PublicKey;
+    let newProviderWallet: Keypair;
+    let newProviderTokenAccount: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+    let jobIndex: anchor.BN;
+    let job: PublicKey;
+
+    async function settle(providerTokenAccount: PublicKey) {
+        await program.methods.jobSettle(jobIndex).accountsStrict({
+            market: marketAccount,
+            job,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            providerAccount,
+            providerTokenAccount,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            owner: payer.publicKey,
+            state: PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId)[0],
+            creditProgramUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramReserve: PublicKey.findProgramAddressSync(
+                [Buffer.from("reserve"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramProviderTier: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgram: creditProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        creditProgram = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        providerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, providerWallet.publicKey);
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+
+        newProviderWallet = Keypair.generate();
+        newProviderTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, newProviderWallet.publicKey);
+
+        // a running job, accepted with the old key
+        jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(5 * 10 ** 12),
+            new anchor.BN(1000)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        await program.methods.jobAccept(jobIndex).accountsStrict({
+            market: marketAccount,
+            job,
+            providerAccount,
+            operator: null,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+    });
+
+    it("cannot rotate to the current key", async () => {
+        try {
+            await program.methods.providerRotateKey(providerWallet.publicKey).accountsStrict({
+                provider: providerAccount,
+                authority: providerWallet.publicKey,
+            }).signers([providerWallet]).rpc();
+            assert.fail("the key rotation should not have started");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidProviderKey");
+        }
+    });
+
+    it("only the provider can rotate its key", async () => {
+        try {
+            await program.methods.providerRotateKey(payer.publicKey).accountsStrict({
+                provider: providerAccount,
+                authority: payer.publicKey,
+            }).rpc();
+            assert.fail("the key rotation should not have started");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
+    });
+
+    it("only the proposed key can accept the rotation", async () => {
+        await program.methods.providerRotateKey(newProviderWallet.publicKey).accountsStrict({
+            provider: providerAccount,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+        expect((await program.account.provider.fetch(providerAccount)).pendingOwner.toBase58())
+            .to.equal(newProviderWallet.publicKey.toBase58());
+
+        try {
+            await program.methods.providerAcceptKey().accountsStrict({
+                provider: providerAccount,
+                newOwner: payer.publicKey,
+            }).rpc();
+            assert.fail("the key rotation should not have been accepted");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
+    });
+
+    it("keeps the provider id and its jobs when the key is rotated", async () => {
+        await program.methods.providerAcceptKey().accountsStrict({
+            provider: providerAccount,
+            newOwner: newProviderWallet.publicKey,
+        }).signers([newProviderWallet]).rpc();
+
+        const providerData = await program.account.provider.fetch(providerAccount);
+        expect(providerData.id.toBase58()).to.equal(providerWallet.publicKey.toBase58());
+        expect(providerData.owner.toBase58()).to.equal(newProviderWallet.publicKey.toBase58());
+        expect(providerData.pendingOwner.toBase58()).to.equal(PublicKey.default.toBase58());
+        expect(providerData.activeJobs.toNumber()).to.equal(1);
+
+        const jobData = await program.account.job.fetch(job);
+        expect(jobData.provider.toBase58()).to.equal(providerWallet.publicKey.toBase58());
+        expect(jobData.status).to.have.property("active");
+    });
+
+    it("pays settlements to the new key", async () => {
+        await new Promise((resolve) => setTimeout(resolve, 2000));
+
+        try {
+            await settle(providerTokenAccount);
+            assert.fail("the old key should not have been paid");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("ConstraintRaw");
+        }
+
+        await settle(newProviderTokenAccount);
+        expect(Number((await getAccount(provider.connection, newProviderTokenAccount)).amount)).to.be.greaterThan(0);
+    });
+
+    it("the old key can no longer act for the provider", async () => {
+        try {
+            await program.methods.jobTerminateByProvider(jobIndex).accountsStrict({
+                market: marketAccount,
+                job,
+                providerAccount,
+                authority: providerWallet.publicKey,
+            }).signers([providerWallet]).rpc();
+            assert.fail("the job should not have been terminated");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
+
+        await program.methods.jobTerminateByProvider(jobIndex).accountsStrict({
+            market: marketAccount,
+            job,
+            providerAccount,
+            authority: newProviderWallet.publicKey,
+        }).signers([newProviderWallet]).rpc();
+        expect((await program.account.job.fetch(job)).status).to.have.property("terminated");
+    });
+});
+
+describe("market_v1 - provider payouts", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let creditProgram: Program<OysterCredits>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let providerTokenAccount: PublicKey;
+    let treasury: PublicKey;
+    let treasuryTokenAccount: PublicKey;
+    let partner: PublicKey;
+    let partnerTokenAccount: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+    let jobIndex: anchor.BN;
+    let job: PublicKey;
+
+    async function setPayout(beneficiaries: any[]) {
+        await program.methods.providerSetPayout(beneficiaries).accountsStrict({
+            provider: providerAccount,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+    }
+
+    async function settle(beneficiaryTokenAccounts: PublicKey[]) {
+        await program.methods.jobSettle(jobIndex).accountsStrict({
+            market: marketAccount,
+            job,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            providerAccount,
+            providerTokenAccount,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            owner: payer.publicKey,
+            state: PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId)[0],
+            creditProgramUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramReserve: PublicKey.findProgramAddressSync(
+                [Buffer.from("reserve"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramProviderTier: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgram: creditProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).remainingAccounts(beneficiaryTokenAccounts.map((pubkey) => ({
+            pubkey,
+            isWritable: true,
+            isSigner: false,
+        }))).rpc();
+    }
+
+    async function balance(tokenAccount: PublicKey): Promise<anchor.BN> {
+        return new anchor.BN((await getAccount(provider.connection, tokenAccount)).amount.toString());
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        creditProgram = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        providerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, providerWallet.publicKey);
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+
+        treasury = Keypair.generate().publicKey;
+        treasuryTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, treasury);
+        partner = Keypair.generate().publicKey;
+        partnerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, partner);
+
+        jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(5 * 10 ** 12),
+            new anchor.BN(10000)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        await program.methods.jobAccept(jobIndex).accountsStrict({
+            market: marketAccount,
+            job,
+            providerAccount,
+            operator: null,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+    });
+
+    it("cannot set an invalid payout configuration", async () => {
+        const invalidConfigs = [
+            // shares not adding up to 100%
+            [{ wallet: treasury, shareBps: 5000 }, { wallet: partner, shareBps: 4000 }],
+            // empty share
+            [{ wallet: treasury, shareBps: 10000 }, { wallet: partner, shareBps: 0 }],
+            // unset wallet
+            [{ wallet: PublicKey.default, shareBps: 10000 }],
+            // too many beneficiaries
+            Array.from({ length: 6 }, () => ({ wallet: Keypair.generate().publicKey, shareBps: 1000 }))
+                .concat([{ wallet: treasury, shareBps: 4000 }]),
+        ];
+        for (const beneficiaries of invalidConfigs) {
+            try {
+                await setPayout(beneficiaries);
+                assert.fail("the payout should not have been set");
+            } catch (error) {
+                expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidPayoutConfig");
+            }
+        }
+    });
+
+    it("only the provider can set its payout", async () => {
+        try {
+            await program.methods.providerSetPayout([{ wallet: treasury, shareBps: 10000 }]).accountsStrict({
+                provider: providerAccount,
+                authority: payer.publicKey,
+            }).rpc();
+            assert.fail("the payout should not have been set");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
+    });
+
+    it("splits settlements between the beneficiaries", async () => {
+        await setPayout([{ wallet: treasury, shareBps: 7500 }, { wallet: partner, shareBps: 2500 }]);
+        const beneficiaries = (await program.account.provider.fetch(providerAccount)).beneficiaries;
+        expect(beneficiaries.length).to.equal(2);
+        expect(beneficiaries[0].wallet.toBase58()).to.equal(treasury.toBase58());
+        expect(beneficiaries[0].shareBps).to.equal(7500);
+
+        await new Promise((resolve) => setTimeout(resolve, 2000));
+
+        // the beneficiary accounts are passed in order
+        for (const accounts of [[], [treasuryTokenAccount], [partnerTokenAccount, treasuryTokenAccount]]) {
+            try {
+                await settle(accounts);
+                assert.fail("the settlement should not have been paid");
+            } catch (error) {
+                expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidBeneficiaryAccount");
+            }
+        }
+
+        const jobDataInitial = await program.account.job.fetch(job);
+        const providerBefore = await balance(providerTokenAccount);
+        await settle([treasuryTokenAccount, partnerTokenAccount]);
+
+        const jobData = await program.account.job.fetch(job);
+        const settled = jobDataInitial.balance.sub(jobData.balance);
+        expect(settled.toNumber()).to.be.greaterThan(0);
+        const treasuryShare = settled.muln(7500).divn(10000);
+        expect((await balance(treasuryTokenAccount)).toString()).to.equal(treasuryShare.toString());
+        expect((await balance(partnerTokenAccount)).toString()).to.equal(settled.sub(treasuryShare).toString());
+        expect((await balance(providerTokenAccount)).toString()).to.equal(providerBefore.toString());
+    });
+
+    it("pays the provider again once the payout is cleared", async () => {
+        await setPayout([]);
+        expect((await program.account.provider.fetch(providerAccount)).beneficiaries.length).to.equal(0);
+
+        await new Promise((resolve) => setTimeout(resolve, 2000));
+        const jobDataInitial = await program.account.job.fetch(job);
+        const providerBefore = await balance(providerTokenAccount);
+        const treasuryBefore = await balance(treasuryTokenAccount);
+        await settle([]);
+
+        const settled = jobDataInitial.balance.sub((await program.account.job.fetch(job)).balance);
+        expect((await balance(providerTokenAccount)).sub(providerBefore).toString()).to.equal(settled.toString());
+        expect((await balance(treasuryTokenAccount)).toString()).to.equal(treasuryBefore.toString());
+    });
+});
+
+describe("market_v1 - provider profile", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let profile: PublicKey;
+    let operatorWallet: Keypair;
+    let operatorAccount: PublicKey;
+
+    // Update the profile, returning the emitted event
+    async function updateProfile(fields: any, authority: Keypair = providerWallet, operator: PublicKey | null = null): Promise<any> {
+        const sig = await program.methods.providerProfileUpdate({
+            cps: null,
+            regions: null,
+            instanceTypes: null,
+            attestationKey: null,
+            metadataUri: null,
+            ...fields,
+        }).accountsStrict({
+            provider: providerAccount,
+            profile,
+            authority: authority.publicKey,
+            operator,
+            systemProgram: SystemProgram.programId,
+        }).signers([authority]).rpc({ commitment: "confirmed" });
+
+        const tx = await provider.connection.getTransaction(sig, {
+            commitment: "confirmed",
+            maxSupportedTransactionVersion: 0,
+        });
+        const events = [...new anchor.EventParser(program.programId, program.coder).parseLogs(tx.meta.logMessages)];
+        return events.find((event) => event.name === "providerProfileUpdated").data;
+    }
+
+    async function expectProfileError(fields: any, code: string, authority: Keypair = providerWallet, operator: PublicKey | null = null) {
+        try {
+            await updateProfile(fields, authority, operator);
+            assert.fail("the profile should not have been updated");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal(code);
+        }
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        [profile,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider_profile"), providerAccount.toBuffer()],
+            program.programId
+        );
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+
+        operatorWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(operatorWallet.publicKey, 10 ** 9)
+        );
+        [operatorAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider_operator"), providerAccount.toBuffer(), operatorWallet.publicKey.toBuffer()],
+            program.programId
+        );
+    });
+
+    it("can create a profile from the control plane", async () => {
+        await program.methods.providerProfileInit().accountsStrict({
+            provider: providerAccount,
+            profile,
+            authority: providerWallet.publicKey,
+            systemProgram: SystemProgram.programId,
+        }).signers([providerWallet]).rpc();
+
+        const profileData = await program.account.providerProfile.fetch(profile);
+        expect(profileData.provider.toBase58()).to.equal(providerAccount.toBase58());
+        expect(profileData.version.toNumber()).to.equal(0);
+        expect(profileData.cps).to.deep.equal(["https://example.com/"]);
+        expect(profileData.regions).to.deep.equal([]);
+        expect(profileData.instanceTypes).to.deep.equal([]);
+        expect(profileData.metadataUri).to.equal("");
+    });
+
+    it("can update the profile and grow the account", async () => {
+        const sizeBefore = (await provider.connection.getAccountInfo(profile)).data.length;
+        const attestationKey = Keypair.generate().publicKey;
+
+        const event = await updateProfile({
+            regions: ["ap-south-1", "us-east-1"],
+            instanceTypes: ["c6a.xlarge"],
+            attestationKey,
+            metadataUri: "https://example.com/provider.json",
+        });
+
+        const profileData = await program.account.providerProfile.fetch(profile);
+        expect(profileData.version.toNumber()).to.equal(1);
+        expect(profileData.regions).to.deep.equal(["ap-south-1", "us-east-1"]);
+        expect(profileData.instanceTypes).to.deep.equal(["c6a.xlarge"]);
+        expect(profileData.attestationKey.toBase58()).to.equal(attestationKey.toBase58());
+        expect(profileData.metadataUri).to.equal("https://example.com/provider.json");
+        expect((await provider.connection.getAccountInfo(profile)).data.length).to.be.greaterThan(sizeBefore);
+
+        expect(event.version.toNumber()).to.equal(1);
+        expect(event.cps).to.be.null;
+        expect(event.regions).to.deep.equal(["ap-south-1", "us-east-1"]);
+    });
+
+    it("only reports the changed fields", async () => {
+        const event = await updateProfile({
+            regions: ["ap-south-1", "us-east-1"],
+            metadataUri: "https://example.com/provider-v2.json",
+        });
+
+        expect(event.version.toNumber()).to.equal(2);
+        expect(event.regions).to.be.null;
+        expect(event.metadataUri).to.equal("https://example.com/provider-v2.json");
+    });
+
+    it("keeps the control plane URL in sync with the first endpoint", async () => {
+        await updateProfile({ cps: ["https://cp1.example.com/", "https://cp2.example.com/"] });
+
+        expect((await program.account.providerProfile.fetch(profile)).cps)
+            .to.deep.equal(["https://cp1.example.com/", "https://cp2.example.com/"]);
+        expect((await program.account.provider.fetch(providerAccount)).cp).to.equal("https://cp1.example.com/");
+    });
+
+    it("shrinks the account when fields are cleared", async () => {
+        const sizeBefore = (await provider.connection.getAccountInfo(profile)).data.length;
+
+        await updateProfile({ regions: [], instanceTypes: [] });
+
+        expect((await program.account.providerProfile.fetch(profile)).regions).to.deep.equal([]);
+        expect((await provider.connection.getAccountInfo(profile)).data.length).to.be.lessThan(sizeBefore);
+    });
+
+    it("cannot set an invalid profile", async () => {
+        await expectProfileError({ cps: [] }, "InvalidControlPlaneUrl");
+        await expectProfileError({ regions: [""] }, "InvalidProfile");
+        await expectProfileError({ instanceTypes: ["x".repeat(101)] }, "InvalidProfile");
+        await expectProfileError({ regions: Array.from({ length: 17 }, (_, i) => `region-${i}`) }, "InvalidProfile");
+        await expectProfileError({ metadataUri: "x".repeat(201) }, "InvalidProfile");
+    });
+
+    it("operators can update the profile but not the attestation key", async () => {
+        await expectProfileError({ regions: ["eu-west-1"] }, "Unauthorized", operatorWallet, null);
+
+        await program.methods.providerOperatorSet(operatorWallet.publicKey, 1).accountsStrict({
+            provider: providerAccount,
+            providerOperator: operatorAccount,
+            authority: providerWallet.publicKey,
+            systemProgram: SystemProgram.programId,
+        }).signers([providerWallet]).rpc();
+
+        await updateProfile({ regions: ["eu-west-1"] }, operatorWallet, operatorAccount);
+        expect((await program.account.providerProfile.fetch(profile)).regions).to.deep.equal(["eu-west-1"]);
+
+        await expectProfileError(
+            { attestationKey: Keypair.generate().publicKey },
+            "Unauthorized",
+            operatorWallet,
+            operatorAccount
+        );
+    });
+});
+
+describe("market_v1 - provider rate cards", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let rateCard: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+
+    async function setRateCard(entries: any[]) {
+        await program.methods.providerRateCardSet(entries).accountsStrict({
+            market: marketAccount,
+            provider: providerAccount,
+            rateCard,
+            authority: providerWallet.publicKey,
+            systemProgram: SystemProgram.programId,
+        }).signers([providerWallet]).rpc();
+    }
+
+    async function openJob(metadata: string, rate: number, withRateCard: boolean = true): Promise<PublicKey> {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            metadata,
+            providerWallet.publicKey,
+            new anchor.BN(rate),
+            new anchor.BN(1000)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: withRateCard ? rateCard : null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        return job;
+    }
+
+    async function expectOpenError(metadata: string, rate: number, code: string, withRateCard: boolean = true) {
+        try {
+            await openJob(metadata, rate, withRateCard);
+            assert.fail("the job should not have been opened");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal(code);
+        }
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        [rateCard,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("rate_card"), providerAccount.toBuffer()],
+            program.programId
+        );
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+    });
+
+    it("accepts any rate without a rate card", async () => {
+        await openJob("metadata example", 1, false);
+    });
+
+    it("cannot set an invalid rate card", async () => {
+        const invalidCards = [
+            [{ instanceType: "", region: "", minRate: new anchor.BN(100) }],
+            [{ instanceType: "x".repeat(33), region: "", minRate: new anchor.BN(100) }],
+            [{ instanceType: "c6a.xlarge", region: "x".repeat(33), minRate: new anchor.BN(100) }],
+            Array.from({ length: 17 }, (_, i) => ({ instanceType: `type-${i}`, region: "", minRate: new anchor.BN(100) })),
+        ];
+        for (const entries of invalidCards) {
+            try {
+                await setRateCard(entries);
+                assert.fail("the rate card should not have been set");
+            } catch (error) {
+                expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidRateCard");
+            }
+        }
+    });
+
+    it("only the provider can set its rate card", async () => {
+        try {
+            await program.methods.providerRateCardSet([]).accountsStrict({
+                market: marketAccount,
+                provider: providerAccount,
+                rateCard,
+                authority: payer.publicKey,
+                systemProgram: SystemProgram.programId,
+            }).rpc();
+            assert.fail("the rate card should not have been set");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
+    });
+
+    it("applies the first rate card immediately", async () => {
+        await setRateCard([
+            { instanceType: "c6a.xlarge", region: "", minRate: new anchor.BN(100) },
+            { instanceType: "c6a.2xlarge", region: "us-east-1", minRate: new anchor.BN(300) },
+        ]);
+
+        expect((await program.account.provider.fetch(providerAccount)).hasRateCard).to.be.true;
+        const rateCardData = await program.account.rateCard.fetch(rateCard);
+        expect(rateCardData.provider.toBase58()).to.equal(providerAccount.toBase58());
+        expect(rateCardData.entries.length).to.equal(2);
+        expect(rateCardData.pendingEntries.length).to.equal(0);
+        expect(rateCardData.effectiveAt.toNumber()).to.equal(0);
+    });
+
+    it("holds new jobs to the rate card", async () => {
+        await expectOpenError('{"instance":"c6a.xlarge"}', 100, "RateCardRequired", false);
+        await expectOpenError("metadata example", 100, "RateCardEntryNotFound");
+        await expectOpenError('{"instance":"m5.large"}', 100, "RateCardEntryNotFound");
+        await expectOpenError('{"instance":"c6a.2xlarge","region":"ap-south-1"}', 300, "RateCardEntryNotFound");
+        await expectOpenError('{"instance":"c6a.xlarge","region":"ap-south-1"}', 99, "RateBelowRateCard");
+        await expectOpenError('{"instance":"c6a.2xlarge","region":"us-east-1"}', 299, "RateBelowRateCard");
+
+        await openJob('{"instance":"c6a.xlarge","region":"ap-south-1"}', 100);
+        await openJob('{"instance":"c6a.2xlarge","region":"us-east-1"}', 300);
+    });
+
+    it("applies later rate card changes after the notice period", async () => {
+        await setRateCard([{ instanceType: "c6a.xlarge", region: "", minRate: new anchor.BN(200) }]);
+
+        const rateCardData = await program.account.rateCard.fetch(rateCard);
+        expect(rateCardData.entries.length).to.equal(2);
+        expect(rateCardData.pendingEntries.length).to.equal(1);
+        expect(rateCardData.effectiveAt.toNumber()).to.be.closeTo(Math.floor(Date.now() / 1000) + 60, 5);
+
+        // the current card still applies during the notice
+        await openJob('{"instance":"c6a.xlarge"}', 100);
+
+        await new Promise((resolve) => setTimeout(resolve, 61000));
+
+        await expectOpenError('{"instance":"c6a.xlarge"}', 100, "RateBelowRateCard");
+        await openJob('{"instance":"c6a.xlarge"}', 200);
+    });
+});
+
+describe("market_v1 - provider bonds", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let bondTokenAccount: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let providerTokenAccount: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+
+    async function openJob(): Promise<any> {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(10),
+            new anchor.BN(1000)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        return { jobIndex, job };
+    }
+
+    async function expectOpenError(code: string) {
+        try {
+            await openJob();
+            assert.fail("the job should not have been opened");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal(code);
+        }
+    }
+
+    async function slash(job: any, amount: number, ownerTokenAccount: PublicKey) {
+        await program.methods.providerSlash(job.jobIndex, new anchor.BN(amount)).accountsStrict({
+            market: marketAccount,
+            admin: payer.publicKey,
+            job: job.job,
+            providerAccount,
+            tokenMint,
+            bondTokenAccount,
+            ownerTokenAccount,
+            jobNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        }).rpc();
+    }
+
+    async function unbondWithdraw() {
+        await program.methods.providerUnbondWithdraw().accountsStrict({
+            market: marketAccount,
+            provider: providerAccount,
+            tokenMint,
+            bondTokenAccount,
+            authorityTokenAccount: providerTokenAccount,
+            authority: providerWallet.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        }).signers([providerWallet]).rpc();
+    }
+
+    async function balance(tokenAccount: PublicKey): Promise<number> {
+        return Number((await getAccount(provider.connection, tokenAccount)).amount);
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+        [bondTokenAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider_bond"), tokenMint.toBuffer()],
+            program.programId
+        );
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        providerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, providerWallet.publicKey);
+        await mintTo(provider.connection, payer, tokenMint, providerTokenAccount, payer, 1000);
+
+        await program.methods.updateBondConfig(new anchor.BN(500), new anchor.BN(2)).accountsStrict({
+            market: marketAccount,
+            admin: payer.publicKey,
+        }).rpc();
+    });
+
+    after(async () => {
+        await program.methods.updateBondConfig(new anchor.BN(0), new anchor.BN(0)).accountsStrict({
+            market: marketAccount,
+            admin: payer.publicKey,
+        }).rpc();
+    });
+
+    it("only the admin can update the bond config", async () => {
+        try {
+            await program.methods.updateBondConfig(new anchor.BN(0), new anchor.BN(0)).accountsStrict({
+                market: marketAccount,
+                admin: providerWallet.publicKey,
+            }).signers([providerWallet]).rpc();
+            assert.fail("the bond config should not have been updated");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
+
+        const marketData = await program.account.market.fetch(marketAccount);
+        expect(marketData.minProviderBond.toNumber()).to.equal(500);
+        expect(marketData.unbondingPeriod.toNumber()).to.equal(2);
+    });
+
+    it("requires the minimum bond to register", async () => {
+        const accounts = await providerAddAccounts(program, providerAccount, providerWallet.publicKey);
+        try {
+            await program.methods.providerAdd("https://example.com/", new anchor.BN(100))
+                .accountsStrict({ ...accounts, authorityTokenAccount: providerTokenAccount })
+                .signers([providerWallet]).rpc();
+            assert.fail("the provider should not have been added");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InsufficientProviderBond");
+        }
+
+        try {
+            await program.methods.providerAdd("https://example.com/", new anchor.BN(500))
+                .accountsStrict(accounts)
+                .signers([providerWallet]).rpc();
+            assert.fail("the provider should not have been added");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("BondAccountRequired");
+        }
+    });
+
+    it("can register with a bond", async () => {
+        const vaultBefore = await balance(bondTokenAccount);
+
+        await program.methods.providerAdd("https://example.com/", new anchor.BN(500))
+            .accountsStrict({
+                ...(await providerAddAccounts(program, providerAccount, providerWallet.publicKey)),
+                authorityTokenAccount: providerTokenAccount,
+            })
+            .signers([providerWallet]).rpc();
+
+        const providerData = await program.account.provider.fetch(providerAccount);
+        expect(providerData.bond.toNumber()).to.equal(500);
+        expect(providerData.bondMint.toBase58()).to.equal(tokenMint.toBase58());
+        expect(await balance(bondTokenAccount) - vaultBefore).to.equal(500);
+        expect(await balance(providerTokenAccount)).to.equal(500);
+
+        await openJob();
+    });
+
+    it("takes no jobs while bonded below the minimum", async () => {
+        await program.methods.providerUnbondRequest(new anchor.BN(100)).accountsStrict({
+            market: marketAccount,
+            provider: providerAccount,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+
+        const providerData = await program.account.provider.fetch(providerAccount);
+        expect(providerData.bond.toNumber()).to.equal(400);
+        expect(providerData.unbondingAmount.toNumber()).to.equal(100);
+        await expectOpenError("InsufficientProviderBond");
+
+        await program.methods.providerBondDeposit(new anchor.BN(100)).accountsStrict({
+            market: marketAccount,
+            provider: providerAccount,
+            tokenMint,
+            bondTokenAccount,
+            authorityTokenAccount: providerTokenAccount,
+            authority: providerWallet.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).signers([providerWallet]).rpc();
+        expect((await program.account.provider.fetch(providerAccount)).bond.toNumber()).to.equal(500);
+        await openJob();
+    });
+
+    it("can withdraw an unbonded amount after the unbonding period", async () => {
+        try {
+            await unbondWithdraw();
+            assert.fail("the bond should not have been withdrawn");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("UnbondingPeriodNotOver");
+        }
+
+        await new Promise((resolve) => setTimeout(resolve, 3000));
+        const providerBefore = await balance(providerTokenAccount);
+        await unbondWithdraw();
+
+        expect(await balance(providerTokenAccount) - providerBefore).to.equal(100);
+        expect((await program.account.provider.fetch(providerAccount)).unbondingAmount.toNumber()).to.equal(0);
+
+        try {
+            await unbondWithdraw();
+            assert.fail("the bond should not have been withdrawn");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("NothingToUnbond");
+        }
+    });
+
+    it("can slash the bond to refund a job owner", async () => {
+        const job = await openJob();
+
+        // the refund goes to the job owner
+        try {
+            await slash(job, 200, providerTokenAccount);
+            assert.fail("the bond should not have been slashed");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
+        try {
+            await slash(job, 501, ownerTokenAccount);
+            assert.fail("the bond should not have been slashed");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidAmount");
+        }
+
+        const ownerBefore = await balance(ownerTokenAccount);
+        await slash(job, 200, ownerTokenAccount);
+
+        expect(await balance(ownerTokenAccount) - ownerBefore).to.equal(200);
+        expect((await program.account.provider.fetch(providerAccount)).bond.toNumber()).to.equal(300);
+        await expectOpenError("InsufficientProviderBond");
+    });
+});
+
+describe("market_v1 - permissioned market", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let creditProgram: Program<OysterCredits>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let providerTokenAccount: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+
+    function applicationAddress(applicant: PublicKey): PublicKey {
+        return PublicKey.findProgramAddressSync(
+            [Buffer.from("provider_application"), applicant.toBuffer()],
+            program.programId
+        )[0];
+    }
+
+    async function apply(applicant: Keypair, cp: string) {
+        await program.methods.providerApply(cp).accountsStrict({
+            application: applicationAddress(applicant.publicKey),
+            applicant: applicant.publicKey,
+            systemProgram: SystemProgram.programId,
+        }).signers([applicant]).rpc();
+    }
+
+    async function register(applicant: Keypair, cp: string, withApplication: boolean = true) {
+        const [account,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), applicant.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods.providerAdd(cp, new anchor.BN(0)).accountsStrict({
+            ...(await providerAddAccounts(program, account, applicant.publicKey)),
+            application: withApplication ? applicationAddress(applicant.publicKey) : null,
+        }).signers([applicant]).rpc();
+    }
+
+    async function expectRegisterError(applicant: Keypair, cp: string, withApplication: boolean, code: string) {
+        try {
+            await register(applicant, cp, withApplication);
+            assert.fail("the provider should not have been added");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal(code);
+        }
+    }
+
+    async function openJob(): Promise<any> {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(10),
+            new anchor.BN(1000)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        return { jobIndex, job };
+    }
+
+    async function accept(job: any) {
+        await program.methods.jobAccept(job.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            providerAccount,
+            operator: null,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+    }
+
+    async function adminAction(method: string, admin: Keypair = payer) {
+        await program.methods[method]().accountsStrict({
+            market: marketAccount,
+            admin: admin.publicKey,
+            provider: providerAccount,
+        }).signers([admin]).rpc();
+    }
+
+    async function setPermissioned(permissioned: boolean) {
+        await program.methods.updatePermissioned(permissioned).accountsStrict({
+            market: marketAccount,
+            admin: payer.publicKey,
+        }).rpc();
+    }
+
+    async function newWallet(): Promise<Keypair> {
+        const wallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(wallet.publicKey, 10 ** 9)
+        );
+        return wallet;
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        creditProgram = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = await newWallet();
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        providerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, providerWallet.publicKey);
+
+        await setPermissioned(true);
+    });
+
+    after(async () => {
+        await setPermissioned(false);
+    });
+
+    it("requires an application to register", async () => {
+        expect((await program.account.market.fetch(marketAccount)).permissioned).to.be.true;
+        await expectRegisterError(providerWallet, "https://example.com/", false, "ProviderNotApproved");
+
+        try {
+            await apply(providerWallet, "");
+            assert.fail("the application should not have been made");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidControlPlaneUrl");
+        }
+        await apply(providerWallet, "https://example.com/");
+
+        const applicationData = await program.account.providerApplication.fetch(applicationAddress(providerWallet.publicKey));
+        expect(applicationData.applicant.toBase58()).to.equal(providerWallet.publicKey.toBase58());
+        expect(applicationData.cp).to.equal("https://example.com/");
+        expect(applicationData.approved).to.be.false;
+        await expectRegisterError(providerWallet, "https://example.com/", true, "ProviderNotApproved");
+    });
+
+    it("only the admin can approve an application", async () => {
+        try {
+            await program.methods.providerApplicationApprove().accountsStrict({
+                market: marketAccount,
+                admin: providerWallet.publicKey,
+                application: applicationAddress(providerWallet.publicKey),
+            }).signers([providerWallet]).rpc();
+            assert.fail("the application should not have been approved");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
+    });
+
+    it("can register with an approved application", async () => {
+        await program.methods.providerApplicationApprove().accountsStrict({
+            market: marketAccount,
+            admin: payer.publicKey,
+            application: applicationAddress(providerWallet.publicKey),
+        }).rpc();
+        expect((await program.account.providerApplication.fetch(applicationAddress(providerWallet.publicKey))).approved)
+            .to.be.true;
+
+        // the reviewed control plane is registered
+        await expectRegisterError(providerWallet, "https://other.example.com/", true, "InvalidControlPlaneUrl");
+        await register(providerWallet, "https://example.com/");
+
+        expect((await program.account.provider.fetch(providerAccount)).cp).to.equal("https://example.com/");
+        expect(await program.account.providerApplication.fetchNullable(applicationAddress(providerWallet.publicKey)))
+            .to.be.null;
+    });
+
+    it("can reject an application", async () => {
+        const applicant = await newWallet();
+        await apply(applicant, "https://example.com/");
+
+        await program.methods.providerApplicationReject().accountsStrict({
+            market: marketAccount,
+            admin: payer.publicKey,
+            application: applicationAddress(applicant.publicKey),
+            applicant: applicant.publicKey,
+        }).rpc();
+
+        expect(await program.account.providerApplication.fetchNullable(applicationAddress(applicant.publicKey))).to.be.null;
+        await expectRegisterError(applicant, "https://example.com/", false, "ProviderNotApproved");
+    });
+
+    it("a suspended provider takes no new jobs but keeps running ones", async () => {
+        const runningJob = await openJob();
+        await accept(runningJob);
+        const pendingJob = await openJob();
+
+        try {
+            await adminAction("providerSuspend", providerWallet);
+            assert.fail("the provider should not have been suspended");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
+        await adminAction("providerSuspend");
+        expect((await program.account.provider.fetch(providerAccount)).suspended).to.be.true;
+
+        try {
+            await openJob();
+            assert.fail("the job should not have been opened");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("ProviderSuspended");
+        }
+        try {
+            await accept(pendingJob);
+            assert.fail("the job should not have been accepted");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("ProviderSuspended");
+        }
+        try {
+            await adminAction("providerSuspend");
+            assert.fail("the provider is already suspended");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("ProviderSuspended");
+        }
+
+        // running jobs are still settled
+        await program.methods.jobSettle(runningJob.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: runningJob.job,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            providerAccount,
+            providerTokenAccount,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            owner: payer.publicKey,
+            state: PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId)[0],
+            creditProgramUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramReserve: PublicKey.findProgramAddressSync(
+                [Buffer.from("reserve"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramProviderTier: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgram: creditProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        expect((await program.account.job.fetch(runningJob.job)).status).to.have.property("active");
+    });
+
+    it("can reinstate a suspended provider", async () => {
+        await adminAction("providerReinstate");
+        expect((await program.account.provider.fetch(providerAccount)).suspended).to.be.false;
+
+        await openJob();
+
+        try {
+            await adminAction("providerReinstate");
+            assert.fail("the provider is not suspended");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("ProviderNotSuspended");
+        }
+    });
+});
+
+describe("market_v1 - job acceptance", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let creditProgram: Program<OysterCredits>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+
+    async function openJob(rate: number, balance: number): Promise<any> {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(rate),
+            new anchor.BN(balance)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        return { jobIndex, job };
+    }
+
+    async function accept(job: any, authority: Keypair = providerWallet) {
+        await program.methods.jobAccept(job.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            providerAccount,
+            operator: null,
+            authority: authority.publicKey,
+        }).signers([authority]).rpc();
+    }
+
+    // Reject or expire a pending job
+    async function cancel(method: string, job: any, authority: Keypair) {
+        await program.methods[method](job.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            topupPolicy: PublicKey.findProgramAddressSync(
+                [Buffer.from("topup_policy"), job.job.toBuffer()],
+                program.programId
+            )[0],
+            owner: payer.publicKey,
+            jobNftMint: null,
+            jobNftAccount: null,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            operator: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            issuerCreditTokenAccount: null,
+            authority: authority.publicKey,
+            state: PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId)[0],
+            creditProgram: creditProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        }).signers([authority]).rpc();
+    }
+
+    async function expectError(call: Promise<any>, code: string) {
+        try {
+            await call;
+            assert.fail("the call should have failed");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal(code);
+        }
+    }
+
+    async function ownerBalance(): Promise<number> {
+        return Number((await getAccount(provider.connection, ownerTokenAccount)).amount);
+    }
+
+    async function setAcceptTimeout(acceptTimeout: number) {
+        await program.methods.updateAcceptTimeout(new anchor.BN(acceptTimeout)).accountsStrict({
+            market: marketAccount,
+            admin: payer.publicKey,
+        }).rpc();
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        creditProgram = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+    });
+
+    after(async () => {
+        await setAcceptTimeout(86400);
+    });
+
+    it("opens jobs pending acceptance", async () => {
+        const job = await openJob(5 * 10 ** 12, 1000);
+
+        const jobData = await program.account.job.fetch(job.job);
+        expect(jobData.status).to.have.property("pending");
+        expect(jobData.acceptDeadline.toNumber()).to.be.closeTo(Math.floor(Date.now() / 1000) + 86400, 5);
+    });
+
+    it("only the provider can accept a job", async () => {
+        const job = await openJob(5 * 10 ** 12, 1000);
+        await expectError(accept(job, payer), "Unauthorized");
+    });
+
+    it("cannot accept a job that does not cover the notice period", async () => {
+        // 5 per second over the 60s notice
+        const job = await openJob(5 * 10 ** 12, 299);
+        await expectError(accept(job), "InsufficientBalance");
+    });
+
+    it("starts billing on acceptance", async () => {
+        const job = await openJob(5 * 10 ** 12, 1000);
+        await new Promise((resolve) => setTimeout(resolve, 2000));
+
+        await accept(job);
+
+        const jobData = await program.account.job.fetch(job.job);
+        expect(jobData.status).to.have.property("active");
+        expect(jobData.acceptDeadline.toNumber()).to.equal(0);
+        expect(jobData.lastSettled.toNumber()).to.be.closeTo(Math.floor(Date.now() / 1000), 2);
+        expect(jobData.balance.toNumber()).to.equal(1000);
+
+        await expectError(accept(job), "JobNotPending");
+        await expectError(cancel("jobReject", job, providerWallet), "JobNotPending");
+    });
+
+    it("refunds a rejected job", async () => {
+        const job = await openJob(5 * 10 ** 12, 1000);
+        const activeJobs = (await program.account.provider.fetch(providerAccount)).activeJobs.toNumber();
+        const ownerBefore = await ownerBalance();
+
+        await expectError(cancel("jobReject", job, payer), "Unauthorized");
+        await cancel("jobReject", job, providerWallet);
+
+        expect(await program.account.job.fetchNullable(job.job)).to.be.null;
+        expect(await ownerBalance() - ownerBefore).to.equal(1000);
+        expect((await program.account.provider.fetch(providerAccount)).activeJobs.toNumber()).to.equal(activeJobs - 1);
+    });
+
+    it("anyone can expire a job past its accept deadline", async () => {
+        await setAcceptTimeout(2);
+        const expiredJob = await openJob(5 * 10 ** 12, 1000);
+        const lateJob = await openJob(5 * 10 ** 12, 1000);
+        const anyone = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(anyone.publicKey, 10 ** 9)
+        );
+
+        await expectError(cancel("jobExpire", expiredJob, anyone), "AcceptDeadlineNotPassed");
+
+        await new Promise((resolve) => setTimeout(resolve, 4000));
+        await expectError(accept(lateJob), "AcceptDeadlinePassed");
+
+        const ownerBefore = await ownerBalance();
+        await cancel("jobExpire", expiredJob, anyone);
+
+        expect(await program.account.job.fetchNullable(expiredJob.job)).to.be.null;
+        expect(await ownerBalance() - ownerBefore).to.equal(1000);
+    });
+});
+
+describe("market_v1 - job status", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let creditProgram: Program<OysterCredits>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let providerTokenAccount: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+    let job: any;
+
+    // Events emitted by a confirmed transaction
+    async function events(sig: string): Promise<any[]> {
+        const tx = await provider.connection.getTransaction(sig, {
+            commitment: "confirmed",
+            maxSupportedTransactionVersion: 0,
+        });
+        return [...new anchor.EventParser(program.programId, program.coder).parseLogs(tx.meta.logMessages)];
+    }
+
+    function creditProgramAccounts(): any {
+        return {
+            state: PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId)[0],
+            creditProgramUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramReserve: PublicKey.findProgramAddressSync(
+                [Buffer.from("reserve"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramProviderTier: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgram: creditProgram.programId,
+        };
+    }
+
+    async function closeJob(): Promise<string> {
+        return await program.methods.jobClose(job.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            topupPolicy: PublicKey.findProgramAddressSync(
+                [Buffer.from("topup_policy"), job.job.toBuffer()],
+                program.programId
+            )[0],
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            jobNftMint: null,
+            jobNftAccount: null,
+            providerTokenAccount,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            issuerCreditTokenAccount: null,
+            owner: payer.publicKey,
+            ...creditProgramAccounts(),
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).rpc({ commitment: "confirmed" });
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        creditProgram = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        providerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, providerWallet.publicKey);
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        job = {
+            jobIndex,
+            job: PublicKey.findProgramAddressSync(
+                [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+                program.programId
+            )[0],
+        };
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(10),
+            new anchor.BN(1000)
+        ).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+
+        await program.methods.updateTombstoneJobs(true).accountsStrict({
+            market: marketAccount,
+            admin: payer.publicKey,
+        }).rpc();
+    });
+
+    after(async () => {
+        await program.methods.updateTombstoneJobs(false).accountsStrict({
+            market: marketAccount,
+            admin: payer.publicKey,
+        }).rpc();
+    });
+
+    it("reports status changes", async () => {
+        expect((await program.account.job.fetch(job.job)).status).to.have.property("pending");
+
+        const sig = await program.methods.jobAccept(job.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            providerAccount,
+            operator: null,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc({ commitment: "confirmed" });
+
+        const event = (await events(sig)).find((event) => event.name === "jobStatusChanged");
+        expect(event.data.job.toBase58()).to.equal(job.job.toBase58());
+        expect(event.data.oldStatus).to.have.property("pending");
+        expect(event.data.newStatus).to.have.property("active");
+        expect((await program.account.job.fetch(job.job)).status).to.have.property("active");
+    });
+
+    it("keeps a tombstone of closed jobs", async () => {
+        expect((await program.account.market.fetch(marketAccount)).tombstoneJobs).to.be.true;
+        const sizeBefore = (await provider.connection.getAccountInfo(job.job)).data.length;
+
+        const sig = await closeJob();
+
+        const event = (await events(sig)).find((event) => event.name === "jobStatusChanged");
+        expect(event.data.oldStatus).to.have.property("active");
+        expect(event.data.newStatus).to.have.property("closed");
+
+        const jobData = await program.account.job.fetch(job.job);
+        expect(jobData.status).to.have.property("closed");
+        expect(jobData.index.toString()).to.equal(job.jobIndex.toString());
+        expect(jobData.owner.toBase58()).to.equal(payer.publicKey.toBase58());
+        expect(jobData.provider.toBase58()).to.equal(providerWallet.publicKey.toBase58());
+        expect(jobData.metadata).to.equal("");
+        expect(jobData.balance.toNumber()).to.equal(0);
+        expect((await provider.connection.getAccountInfo(job.job)).data.length).to.be.lessThan(sizeBefore);
+    });
+
+    it("rejects instructions on a closed job", async () => {
+        try {
+            await closeJob();
+            assert.fail("the job should not have been closed again");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("JobClosed");
+        }
+
+        try {
+            await program.methods.jobSettle(job.jobIndex).accountsStrict({
+                market: marketAccount,
+                job: job.job,
+                tokenMint,
+                programTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("job_token"), tokenMint.toBuffer()],
+                    program.programId
+                )[0],
+                providerAccount,
+                providerTokenAccount,
+                creditMint,
+                programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("credit_token"), creditMint.toBuffer()],
+                    program.programId
+                )[0],
+                owner: payer.publicKey,
+                ...creditProgramAccounts(),
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            }).rpc();
+            assert.fail("the job should not have been settled");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("JobClosed");
+        }
+
+        try {
+            await program.methods.jobDeposit(job.jobIndex, new anchor.BN(100)).accountsStrict({
+                market: marketAccount,
+                job: job.job,
+                owner: payer.publicKey,
+                tokenMint,
+                ownerTokenAccount,
+                providerAccount,
+                providerTokenAccount,
+                programTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("job_token"), tokenMint.toBuffer()],
+                    program.programId
+                )[0],
+                userTokenAccount: ownerTokenAccount,
+                creditMint,
+                programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                    [Buffer.from("credit_token"), creditMint.toBuffer()],
+                    program.programId
+                )[0],
+                userCreditTokenAccount: ownerCreditTokenAccount,
+                ...creditProgramAccounts(),
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            }).rpc();
+            assert.fail("the deposit should have been rejected");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("JobClosed");
+        }
+    });
+});
+
+describe("market_v1 - job close request", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let creditProgram: Program<OysterCredits>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let providerTokenAccount: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+    let activeJob: any;
+    let pendingJob: any;
+
+    async function openJob(): Promise<any> {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(5 * 10 ** 12),
+            new anchor.BN(10000)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        return { jobIndex, job };
+    }
+
+    function creditProgramAccounts(): any {
+        return {
+            state: PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId)[0],
+            creditProgramUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramReserve: PublicKey.findProgramAddressSync(
+                [Buffer.from("reserve"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramProviderTier: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgram: creditProgram.programId,
+        };
+    }
+
+    async function closeRequest(job: any) {
+        await program.methods.jobCloseRequest(job.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            providerAccount,
+            providerTokenAccount,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            owner: payer.publicKey,
+            jobNftAccount: null,
+            ...creditProgramAccounts(),
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+    }
+
+    async function closeFinalize(job: any) {
+        await program.methods.jobCloseFinalize(job.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            topupPolicy: PublicKey.findProgramAddressSync(
+                [Buffer.from("topup_policy"), job.job.toBuffer()],
+                program.programId
+            )[0],
+            owner: payer.publicKey,
+            jobNftMint: null,
+            jobNftAccount: null,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            providerTokenAccount,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            issuerCreditTokenAccount: null,
+            authority: payer.publicKey,
+            ...creditProgramAccounts(),
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+    }
+
+    async function expectError(call: Promise<any>, code: string) {
+        try {
+            await call;
+            assert.fail("the call should have failed");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal(code);
+        }
+    }
+
+    async function balance(tokenAccount: PublicKey): Promise<number> {
+        return Number((await getAccount(provider.connection, tokenAccount)).amount);
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        creditProgram = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        providerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, providerWallet.publicKey);
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+
+        activeJob = await openJob();
+        await program.methods.jobAccept(activeJob.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: activeJob.job,
+            providerAccount,
+            operator: null,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+        pendingJob = await openJob();
+    });
+
+    it("can only request to close an active job", async () => {
+        await expectError(closeRequest(pendingJob), "InvalidJobStatus");
+    });
+
+    it("bills usage so far and starts the notice on a close request", async () => {
+        await new Promise((resolve) => setTimeout(resolve, 2000));
+        const jobDataInitial = await program.account.job.fetch(activeJob.job);
+
+        await closeRequest(activeJob);
+
+        const jobData = await program.account.job.fetch(activeJob.job);
+        expect(jobData.status).to.have.property("closing");
+        expect(jobData.endAt.toNumber()).to.equal(jobData.lastSettled.toNumber() + 60);
+        const billed = jobData.lastSettled.sub(jobDataInitial.lastSettled).muln(5).toNumber();
+        expect(billed).to.be.greaterThan(0);
+        expect(jobData.balance.toNumber()).to.equal(10000 - billed);
+        expect(await balance(providerTokenAccount)).to.equal(billed);
+
+        await expectError(closeRequest(activeJob), "InvalidJobStatus");
+    });
+
+    it("cannot deposit into a closing job", async () => {
+        await expectError(program.methods.jobDeposit(activeJob.jobIndex, new anchor.BN(100)).accountsStrict({
+            market: marketAccount,
+            job: activeJob.job,
+            owner: payer.publicKey,
+            tokenMint,
+            ownerTokenAccount,
+            providerAccount,
+            providerTokenAccount,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            ...creditProgramAccounts(),
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).rpc(), "JobClosing");
+    });
+
+    it("cannot finalize before the notice period", async () => {
+        await expectError(closeFinalize(pendingJob), "JobNotClosing");
+        await expectError(closeFinalize(activeJob), "NoticePeriodNotOver");
+    });
+
+    it("bills the notice period and refunds the rest on finalize", async () => {
+        await new Promise((resolve) => setTimeout(resolve, 61000));
+        const jobBalance = (await program.account.job.fetch(activeJob.job)).balance.toNumber();
+        const providerBefore = await balance(providerTokenAccount);
+        const ownerBefore = await balance(ownerTokenAccount);
+
+        await closeFinalize(activeJob);
+
+        // 5 per second over the 60s notice, however late the finalize
+        expect(await balance(providerTokenAccount) - providerBefore).to.equal(300);
+        expect(await balance(ownerTokenAccount) - ownerBefore).to.equal(jobBalance - 300);
+        expect(await program.account.job.fetchNullable(activeJob.job)).to.be.null;
+    });
+});
+
+describe("market_v1 - job reaping", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let creditProgram: Program<OysterCredits>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let providerTokenAccount: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+    let keeper: Keypair;
+
+    // Open a job billed 1 token per second
+    async function openJob(balance: number, accept: boolean = true): Promise<any> {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(10 ** 12),
+            new anchor.BN(balance)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        if (accept) {
+            await program.methods.jobAccept(jobIndex).accountsStrict({
+                market: marketAccount,
+                job,
+                providerAccount,
+                operator: null,
+                authority: providerWallet.publicKey,
+            }).signers([providerWallet]).rpc();
+        }
+        return { jobIndex, job };
+    }
+
+    async function reap(job: any): Promise<string> {
+        return await program.methods.jobReap(job.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            topupPolicy: PublicKey.findProgramAddressSync(
+                [Buffer.from("topup_policy"), job.job.toBuffer()],
+                program.programId
+            )[0],
+            owner: payer.publicKey,
+            jobNftMint: null,
+            jobNftAccount: null,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            providerTokenAccount,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            issuerCreditTokenAccount: null,
+            authority: keeper.publicKey,
+            state: PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId)[0],
+            creditProgramUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramReserve: PublicKey.findProgramAddressSync(
+                [Buffer.from("reserve"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramProviderTier: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgram: creditProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).signers([keeper]).rpc({ commitment: "confirmed" });
+    }
+
+    async function setReapBounty(reapBounty: number) {
+        await program.methods.updateReapBounty(new anchor.BN(reapBounty)).accountsStrict({
+            market: marketAccount,
+            admin: payer.publicKey,
+        }).rpc();
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        creditProgram = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        providerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, providerWallet.publicKey);
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+
+        keeper = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(keeper.publicKey, 10 ** 9)
+        );
+
+        await setReapBounty(10000);
+    });
+
+    after(async () => {
+        await setReapBounty(0);
+    });
+
+    it("cannot reap a job covering its notice period", async () => {
+        const job = await openJob(1000);
+        try {
+            await reap(job);
+            assert.fail("the job should not have been reaped");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("JobNotReapable");
+        }
+    });
+
+    it("cannot reap a pending job", async () => {
+        const job = await openJob(1000, false);
+        try {
+            await reap(job);
+            assert.fail("the job should not have been reaped");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidJobStatus");
+        }
+    });
+
+    it("anyone can reap an exhausted job for the bounty", async () => {
+        // just covers the 60s notice when accepted
+        const job = await openJob(61);
+        await new Promise((resolve) => setTimeout(resolve, 3000));
+        const activeJobs = (await program.account.provider.fetch(providerAccount)).activeJobs.toNumber();
+        const keeperBefore = await provider.connection.getBalance(keeper.publicKey, "confirmed");
+        const providerBefore = Number((await getAccount(provider.connection, providerTokenAccount)).amount);
+        const ownerBefore = Number((await getAccount(provider.connection, ownerTokenAccount)).amount);
+
+        const sig = await reap(job);
+
+        const tx = await provider.connection.getTransaction(sig, {
+            commitment: "confirmed",
+            maxSupportedTransactionVersion: 0,
+        });
+        const keeperAfter = await provider.connection.getBalance(keeper.publicKey, "confirmed");
+        expect(keeperAfter - keeperBefore + tx.meta.fee).to.equal(10000);
+
+        const event = [...new anchor.EventParser(program.programId, program.coder).parseLogs(tx.meta.logMessages)]
+            .find((event) => event.name === "jobReaped");
+        expect(event.data.job.toBase58()).to.equal(job.job.toBase58());
+        expect(event.data.provider.toBase58()).to.equal(providerWallet.publicKey.toBase58());
+        expect(event.data.keeper.toBase58()).to.equal(keeper.publicKey.toBase58());
+        expect(event.data.bounty.toNumber()).to.equal(10000);
+
+        // usage is paid and the rest refunded
+        const paid = Number((await getAccount(provider.connection, providerTokenAccount)).amount) - providerBefore;
+        expect(paid).to.be.greaterThan(0);
+        expect(Number((await getAccount(provider.connection, ownerTokenAccount)).amount) - ownerBefore)
+            .to.equal(event.data.refunded.toNumber());
+        expect(paid + event.data.refunded.toNumber()).to.equal(61);
+
+        expect(await program.account.job.fetchNullable(job.job)).to.be.null;
+        expect((await program.account.provider.fetch(providerAccount)).activeJobs.toNumber()).to.equal(activeJobs - 1);
+    });
+});
+
+describe("market_v1 - job settlement preview", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let creditProgram: Program<OysterCredits>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let owner: Keypair;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+    let jobIndex: anchor.BN;
+    let job: PublicKey;
+
+    async function preview(): Promise<any> {
+        return await program.methods.jobSettlementPreview(jobIndex).accountsStrict({
+            market: marketAccount,
+            job,
+        }).view();
+    }
+
+    async function runway(): Promise<anchor.BN> {
+        return await program.methods.jobRunway(jobIndex).accountsStrict({
+            market: marketAccount,
+            job,
+        }).view();
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        creditProgram = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+
+        // the owner funds the job with 10 credits and 990 tokens
+        owner = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(owner.publicKey, 10 ** 9)
+        );
+        ownerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, owner.publicKey);
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, creditMint, owner.publicKey);
+        const state = PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId)[0];
+        const minterRole = PublicKey.findProgramAddressSync(
+            [Buffer.from("minter"), payer.publicKey.toBuffer()],
+            creditProgram.programId
+        )[0];
+        await creditProgram.methods.addMinter(
+            payer.publicKey,
+            new anchor.BN(10 ** 9),
+            new anchor.BN(86400),
+            new anchor.BN(10 ** 12)
+        ).accountsStrict({
+            state,
+            minterRole,
+            authority: payer.publicKey,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        await creditProgram.methods.mint(new anchor.BN(10)).accountsStrict({
+            state,
+            minterRole,
+            creditMint,
+            tokenAccount: ownerCreditTokenAccount,
+            signer: payer.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        }).rpc();
+        await approve(provider.connection, payer, ownerCreditTokenAccount, payer.publicKey, owner, 10);
+
+        jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(5 * 10 ** 12),
+            new anchor.BN(1000)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: owner.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).signers([owner]).rpc();
+        expect((await program.account.job.fetch(job)).creditBalance.toNumber()).to.equal(10);
+    });
+
+    it("previews a pending job", async () => {
+        const jobPreview = await preview();
+
+        // nothing is billed or reserved before acceptance
+        expect(jobPreview.amountDue.toNumber()).to.equal(0);
+        expect(jobPreview.creditAmount.toNumber()).to.equal(0);
+        expect(jobPreview.tokenAmount.toNumber()).to.equal(0);
+        expect(jobPreview.refundableBalance.toNumber()).to.equal(1000);
+        expect(jobPreview.runway.toNumber()).to.equal(200);
+        expect((await runway()).toNumber()).to.equal(200);
+    });
+
+    it("previews settling an active job", async () => {
+        await program.methods.jobAccept(jobIndex).accountsStrict({
+            market: marketAccount,
+            job,
+            providerAccount,
+            operator: null,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+        await new Promise((resolve) => setTimeout(resolve, 4000));
+
+        const jobPreview = await preview();
+
+        // 5 per second, credits paying first
+        const amountDue = jobPreview.amountDue.toNumber();
+        expect(amountDue).to.be.greaterThan(10);
+        expect(amountDue % 5).to.equal(0);
+        expect(jobPreview.creditAmount.toNumber()).to.equal(10);
+        expect(jobPreview.tokenAmount.toNumber()).to.equal(amountDue - 10);
+        // the 60s notice stays reserved
+        expect(jobPreview.refundableBalance.toNumber()).to.equal(1000 - amountDue - 300);
+        expect(jobPreview.runway.toNumber()).to.equal((1000 - amountDue) / 5);
+
+        // previews do not settle
+        const jobData = await program.account.job.fetch(job);
+        expect(jobData.balance.toNumber()).to.equal(1000);
+        expect(jobData.creditBalance.toNumber()).to.equal(10);
+    });
+
+    it("returns the runway of an active job", async () => {
+        const jobRunway = (await runway()).toNumber();
+        const jobPreview = await preview();
+
+        expect(jobRunway).to.be.lessThan(200);
+        expect(jobRunway).to.be.closeTo(jobPreview.runway.toNumber(), 1);
+    });
+});
+
+describe("market_v1 - job low balance", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let creditProgram: Program<OysterCredits>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let providerTokenAccount: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+
+    // Open and accept a job billed 1 token per second
+    async function openJob(balance: number): Promise<any> {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(10 ** 12),
+            new anchor.BN(balance)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        await program.methods.jobAccept(jobIndex).accountsStrict({
+            market: marketAccount,
+            job,
+            providerAccount,
+            operator: null,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+        return { jobIndex, job };
+    }
+
+    function creditProgramAccounts(): any {
+        return {
+            state: PublicKey.findProgramAddressSync([Buffer.from("state")], creditProgram.programId)[0],
+            creditProgramUsdcTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("program_usdc"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramReserve: PublicKey.findProgramAddressSync(
+                [Buffer.from("reserve"), tokenMint.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgramProviderTier: PublicKey.findProgramAddressSync(
+                [Buffer.from("provider_tier"), providerWallet.publicKey.toBuffer()],
+                creditProgram.programId
+            )[0],
+            creditProgram: creditProgram.programId,
+        };
+    }
+
+    // Low balance warnings emitted by a confirmed transaction
+    async function lowBalanceEvents(sig: string): Promise<any[]> {
+        const tx = await provider.connection.getTransaction(sig, {
+            commitment: "confirmed",
+            maxSupportedTransactionVersion: 0,
+        });
+        return [...new anchor.EventParser(program.programId, program.coder).parseLogs(tx.meta.logMessages)]
+            .filter((event) => event.name === "jobLowBalance")
+            .map((event) => event.data);
+    }
+
+    async function setThreshold(job: any, runwayThreshold: number, owner: Keypair = payer): Promise<string> {
+        return await program.methods.jobSetRunwayThreshold(job.jobIndex, new anchor.BN(runwayThreshold)).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            owner: owner.publicKey,
+            jobNftAccount: null,
+        }).signers([owner]).rpc({ commitment: "confirmed" });
+    }
+
+    async function settle(job: any): Promise<string> {
+        return await program.methods.jobSettle(job.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            providerAccount,
+            providerTokenAccount,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            owner: payer.publicKey,
+            ...creditProgramAccounts(),
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).rpc({ commitment: "confirmed" });
+    }
+
+    async function deposit(job: any, amount: number): Promise<string> {
+        return await program.methods.jobDeposit(job.jobIndex, new anchor.BN(amount)).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            owner: payer.publicKey,
+            tokenMint,
+            ownerTokenAccount,
+            providerAccount,
+            providerTokenAccount,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            ...creditProgramAccounts(),
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).rpc({ commitment: "confirmed" });
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        creditProgram = anchor.workspace.OysterCredits as Program<OysterCredits>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        providerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, providerWallet.publicKey);
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+    });
+
+    it("only the owner can set the runway threshold", async () => {
+        const job = await openJob(200);
+        try {
+            await setThreshold(job, 300, providerWallet);
+            assert.fail("the runway threshold should not have been set");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
+    });
+
+    it("warns when the runway crosses below the threshold", async () => {
+        const job = await openJob(200);
+
+        let events = await lowBalanceEvents(await setThreshold(job, 300));
+        expect((await program.account.job.fetch(job.job)).runwayThreshold.toNumber()).to.equal(300);
+        expect(events.length).to.equal(1);
+        expect(events[0].job.toBase58()).to.equal(job.job.toBase58());
+        expect(events[0].runway.toNumber()).to.be.closeTo(200, 5);
+        expect(events[0].runwayThreshold.toNumber()).to.equal(300);
+        expect(events[0].belowNotice).to.be.false;
+
+        // only crossings are reported
+        events = await lowBalanceEvents(await settle(job));
+        expect(events.length).to.equal(0);
+
+        // topping up re-arms the warning
+        events = await lowBalanceEvents(await deposit(job, 1000));
+        expect(events.length).to.equal(0);
+        events = await lowBalanceEvents(await setThreshold(job, 2000));
+        expect(events.length).to.equal(1);
+        expect(events[0].runwayThreshold.toNumber()).to.equal(2000);
+    });
+
+    it("warns when the runway crosses below the notice period", async () => {
+        // the notice period is 60s
+        const job = await openJob(65);
+        let events = await lowBalanceEvents(await settle(job));
+        expect(events.length).to.equal(0);
+
+        await new Promise((resolve) => setTimeout(resolve, 8000));
+        events = await lowBalanceEvents(await settle(job));
+        expect(events.length).to.equal(1);
+        expect(events[0].runway.toNumber()).to.be.lessThan(60);
+        expect(events[0].belowNotice).to.be.true;
+
+        await new Promise((resolve) => setTimeout(resolve, 1000));
+        events = await lowBalanceEvents(await settle(job));
+        expect(events.length).to.equal(0);
+    });
+});
+
+describe("market_v1 - job auto top-up", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let providerTokenAccount: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+
+    // Open and accept a job billed 1 token per second
+    async function openJob(balance: number): Promise<any> {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(10 ** 12),
+            new anchor.BN(balance)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: payer.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).rpc();
+        await program.methods.jobAccept(jobIndex).accountsStrict({
+            market: marketAccount,
+            job,
+            providerAccount,
+            operator: null,
+            authority: providerWallet.publicKey,
+        }).signers([providerWallet]).rpc();
+        return { jobIndex, job };
+    }
+
+    function topupPolicy(job: any): PublicKey {
+        return PublicKey.findProgramAddressSync(
+            [Buffer.from("topup_policy"), job.job.toBuffer()],
+            program.programId
+        )[0];
+    }
+
+    async function setPolicy(job: any, targetRunway: number, maxPerTopup: number, monthlyCap: number, owner: Keypair = payer) {
+        await program.methods.jobTopupPolicySet(
+            job.jobIndex,
+            new anchor.BN(targetRunway),
+            new anchor.BN(maxPerTopup),
+            new anchor.BN(monthlyCap)
+        ).accountsStrict({
+            job: job.job,
+            policy: topupPolicy(job),
+            owner: owner.publicKey,
+            jobNftAccount: null,
+            systemProgram: SystemProgram.programId,
+        }).signers([owner]).rpc();
+    }
+
+    async function autoTopup(job: any, keeper: Keypair) {
+        await program.methods.jobAutoTopup(job.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            policy: topupPolicy(job),
+            topupDelegate: PublicKey.findProgramAddressSync([Buffer.from("topup_delegate")], program.programId)[0],
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            keeper: keeper.publicKey,
+            jobNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        }).signers([keeper]).rpc();
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, tokenMint, payer.publicKey)).address;
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = (await getOrCreateAssociatedTokenAccount(provider.connection, payer, creditMint, payer.publicKey)).address;
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        providerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, providerWallet.publicKey);
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+
+    it("only the owner can set a valid top-up policy", async () => {
+        const job = await openJob(1000);
+        try {
+            await setPolicy(job, 5000, 400, 600, providerWallet);
+            assert.fail("the top-up policy should not have been set");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("Unauthorized");
+        }
+
+        for (const [targetRunway, maxPerTopup, monthlyCap] of [[0, 400, 600], [5000, 0, 600], [5000, 400, 300]]) {
+            try {
+                await setPolicy(job, targetRunway, maxPerTopup, monthlyCap);
+                assert.fail("the top-up policy should have been rejected");
+            } catch (error) {
+                expect((error as anchor.AnchorError).error.errorCode.code).to.equal("InvalidTopupPolicy");
+            }
+        }
+    });
+
+    it("tops a job up within the policy limits", async () => {
+        const job = await openJob(1000);
+        const keeper = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(keeper.publicKey, 10 ** 9)
+        );
+
+        // runway of about 1000s already covers the target
+        await setPolicy(job, 500, 400, 600);
+        let policyData = await program.account.jobTopupPolicy.fetch(topupPolicy(job));
+        expect(policyData.job.toBase58()).to.equal(job.job.toBase58());
+        expect(policyData.owner.toBase58()).to.equal(payer.publicKey.toBase58());
+        try {
+            await autoTopup(job, keeper);
+            assert.fail("the job should not have been topped up");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("TopupNotNeeded");
+        }
+
+        await setPolicy(job, 5000, 400, 600);
+        const topupDelegate = PublicKey.findProgramAddressSync([Buffer.from("topup_delegate")], program.programId)[0];
+        await approve(provider.connection, payer, ownerTokenAccount, topupDelegate, payer, 10 ** 6);
+        await approve(provider.connection, payer, ownerCreditTokenAccount, topupDelegate, payer, 10 ** 6);
+
+        // capped by the largest single top-up
+        const ownerBalance = (await getAccount(provider.connection, ownerTokenAccount)).amount;
+        let balance = (await program.account.job.fetch(job.job)).balance.toNumber();
+        await autoTopup(job, keeper);
+        let jobData = await program.account.job.fetch(job.job);
+        expect(jobData.balance.toNumber()).to.be.closeTo(balance + 400, 10);
+        policyData = await program.account.jobTopupPolicy.fetch(topupPolicy(job));
+        expect(policyData.periodToppedUp.toNumber()).to.equal(400);
+
+        // then by what is left of the monthly cap
+        balance = jobData.balance.toNumber();
+        await autoTopup(job, keeper);
+        jobData = await program.account.job.fetch(job.job);
+        expect(jobData.balance.toNumber()).to.be.closeTo(balance + 200, 10);
+        policyData = await program.account.jobTopupPolicy.fetch(topupPolicy(job));
+        expect(policyData.periodToppedUp.toNumber()).to.equal(600);
+
+        try {
+            await autoTopup(job, keeper);
+            assert.fail("the monthly cap should have been reached");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("TopupCapReached");
+        }
+        const ownerBalanceAfter = (await getAccount(provider.connection, ownerTokenAccount)).amount;
+        expect(Number(ownerBalance) - Number(ownerBalanceAfter)).to.be.at.most(600);
+    });
+
+    it("stops top-ups once the policy is removed", async () => {
+        const job = await openJob(1000);
+        await setPolicy(job, 5000, 400, 600);
+
+        await program.methods.jobTopupPolicyRemove(job.jobIndex).accountsStrict({
+            job: job.job,
+            policy: topupPolicy(job),
+            owner: payer.publicKey,
+            jobNftAccount: null,
+        }).rpc();
+        expect(await program.account.jobTopupPolicy.fetchNullable(topupPolicy(job))).to.be.null;
+
+        try {
+            await autoTopup(job, payer);
+            assert.fail("the job should not have been topped up");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal("AccountNotInitialized");
+        }
+    });
+});
+
+describe("market_v1 - job sponsors", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let ownerWallet: Keypair;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+    let sponsorWallet: Keypair;
+    let sponsorTokenAccount: PublicKey;
+
+    async function openJob(): Promise<any> {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(10),
+            new anchor.BN(100)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: ownerWallet.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).signers([ownerWallet]).rpc();
+        return { jobIndex, job };
+    }
+
+    async function expectError(call: Promise<any>, code: string) {
+        try {
+            await call;
+            assert.fail("the call should have failed");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal(code);
+        }
+    }
+
+    function sponsorshipAddress(job: PublicKey, seq: number): PublicKey {
+        return PublicKey.findProgramAddressSync(
+            [Buffer.from("job_sponsor"), job.toBuffer(), new anchor.BN(seq).toArrayLike(Buffer, "le", 8)],
+            program.programId
+        )[0];
+    }
+
+    function sponsorDeposit(job: any, seq: number, amount: number): Promise<string> {
+        return program.methods.jobSponsorDeposit(
+            job.jobIndex,
+            new anchor.BN(amount)
+        ).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            sponsorship: sponsorshipAddress(job.job, seq),
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            sponsorTokenAccount,
+            sponsor: sponsorWallet.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).signers([sponsorWallet]).rpc();
+    }
+
+    function rejectAccounts(job: any): any {
+        return {
+            market: marketAccount,
+            job: job.job,
+            topupPolicy: PublicKey.findProgramAddressSync(
+                [Buffer.from("topup_policy"), job.job.toBuffer()],
+                program.programId
+            )[0],
+            owner: ownerWallet.publicKey,
+            jobNftMint: null,
+            jobNftAccount: null,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            operator: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            issuerCreditTokenAccount: null,
+            authority: providerWallet.publicKey,
+            state: PublicKey.findProgramAddressSync(
+                [Buffer.from("state")],
+                anchor.workspace.OysterCredits.programId
+            )[0],
+            creditProgram: anchor.workspace.OysterCredits.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        };
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(ownerWallet.publicKey, 10 ** 9)
+        );
+        ownerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, ownerWallet.publicKey);
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, creditMint, ownerWallet.publicKey);
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+
+        sponsorWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(sponsorWallet.publicKey, 10 ** 9)
+        );
+        sponsorTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, sponsorWallet.publicKey);
+        await mintTo(provider.connection, payer, tokenMint, sponsorTokenAccount, payer, 10 ** 6);
+    });
+
+    after(async () => {
+        await program.methods.updateMinSponsorDeposit(new anchor.BN(0)).accountsStrict({
+            market: marketAccount,
+            admin: payer.publicKey,
+        }).rpc();
+    });
+
+    it("can sponsor a job and refund the sponsorship", async () => {
+        const job = await openJob();
+        await sponsorDeposit(job, 0, 50);
+
+        let jobData = await program.account.job.fetch(job.job);
+        expect(jobData.balance.toNumber()).to.equal(150);
+        expect(jobData.sponsoredBalance.toNumber()).to.equal(50);
+        expect(jobData.sponsorsOutstanding.toNumber()).to.equal(1);
+        const sponsorship = await program.account.jobSponsorship.fetch(sponsorshipAddress(job.job, 0));
+        expect(sponsorship.sponsor.toBase58()).to.equal(sponsorWallet.publicKey.toBase58());
+        expect(sponsorship.amount.toNumber()).to.equal(50);
+
+        await program.methods.jobSponsorRefund(job.jobIndex).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            sponsorship: sponsorshipAddress(job.job, 0),
+            sponsor: sponsorWallet.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            sponsorTokenAccount,
+            authority: sponsorWallet.publicKey,
+            jobNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        }).signers([sponsorWallet]).rpc();
+
+        jobData = await program.account.job.fetch(job.job);
+        expect(jobData.balance.toNumber()).to.equal(100);
+        expect(jobData.sponsorsOutstanding.toNumber()).to.equal(0);
+        expect((await getAccount(provider.connection, sponsorTokenAccount)).amount.toString()).to.equal((10 ** 6).toString());
+        expect(await program.account.jobSponsorship.fetchNullable(sponsorshipAddress(job.job, 0))).to.be.null;
+    });
+
+    it("cannot sponsor below the market minimum", async () => {
+        const job = await openJob();
+        await program.methods.updateMinSponsorDeposit(new anchor.BN(1000)).accountsStrict({
+            market: marketAccount,
+            admin: payer.publicKey,
+        }).rpc();
+        expect((await program.account.market.fetch(marketAccount)).minSponsorDeposit.toNumber()).to.equal(1000);
+
+        await expectError(sponsorDeposit(job, 0, 50), "InvalidAmount");
+    });
+
+    it("refunds outstanding sponsors when the provider rejects the job", async () => {
+        const job = await openJob();
+        await sponsorDeposit(job, 0, 50);
+        const ownerBalanceBefore = (await getAccount(provider.connection, ownerTokenAccount)).amount;
+
+        // sponsorships must be passed along
+        await expectError(
+            program.methods.jobReject(job.jobIndex).accountsStrict(rejectAccounts(job))
+                .signers([providerWallet]).rpc(),
+            "SponsorsOutstanding"
+        );
+
+        await program.methods.jobReject(job.jobIndex).accountsStrict(rejectAccounts(job))
+            .remainingAccounts([
+                { pubkey: sponsorshipAddress(job.job, 0), isWritable: true, isSigner: false },
+                { pubkey: sponsorTokenAccount, isWritable: true, isSigner: false },
+                { pubkey: sponsorWallet.publicKey, isWritable: true, isSigner: false },
+            ])
+            .signers([providerWallet]).rpc();
+
+        expect((await getAccount(provider.connection, sponsorTokenAccount)).amount.toString()).to.equal((10 ** 6).toString());
+        const ownerBalanceAfter = (await getAccount(provider.connection, ownerTokenAccount)).amount;
+        expect(new anchor.BN(ownerBalanceAfter.toString())
+                .sub(new anchor.BN(ownerBalanceBefore.toString())).toString())
+            .to.eq("100");
+        expect(await program.account.jobSponsorship.fetchNullable(sponsorshipAddress(job.job, 0))).to.be.null;
+    });
+});
+
+describe("market_v1 - job ownership transfer", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let ownerWallet: Keypair;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+
+    async function openJob(): Promise<any> {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(10),
+            new anchor.BN(100)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: ownerWallet.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint: null,
+            ownerNftAccount: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: null,
+            systemProgram: SystemProgram.programId,
+        }).signers([ownerWallet]).rpc();
+        return { jobIndex, job };
+    }
+
+    async function expectError(call: Promise<any>, code: string) {
+        try {
+            await call;
+            assert.fail("the call should have failed");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal(code);
+        }
+    }
+
+    function transferOwnership(job: any, newOwner: PublicKey, signer: Keypair = ownerWallet): Promise<string> {
+        return program.methods.jobTransferOwnership(job.jobIndex, newOwner).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            owner: signer.publicKey,
+            jobNftAccount: null,
+        }).signers([signer]).rpc();
+    }
+
+    function acceptOwnership(job: any, newOwner: Keypair): Promise<string> {
+        return program.methods.jobAcceptOwnership(job.jobIndex).accountsStrict({
+            job: job.job,
+            newOwner: newOwner.publicKey,
+        }).signers([newOwner]).rpc();
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(ownerWallet.publicKey, 10 ** 9)
+        );
+        ownerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, ownerWallet.publicKey);
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, creditMint, ownerWallet.publicKey);
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+    });
+
+    it("can transfer and accept job ownership", async () => {
+        const job = await openJob();
+        const newOwner = Keypair.generate();
+
+        await transferOwnership(job, newOwner.publicKey);
+        let jobData = await program.account.job.fetch(job.job);
+        expect(jobData.pendingOwner.toBase58()).to.equal(newOwner.publicKey.toBase58());
+        // the current owner keeps the job until the transfer is accepted
+        expect(jobData.owner.toBase58()).to.equal(ownerWallet.publicKey.toBase58());
+
+        await acceptOwnership(job, newOwner);
+
+        jobData = await program.account.job.fetch(job.job);
+        expect(jobData.owner.toBase58()).to.equal(newOwner.publicKey.toBase58());
+        expect(jobData.pendingOwner.toBase58()).to.equal(PublicKey.default.toBase58());
+        expect(jobData.balance.toNumber()).to.equal(100);
+
+        // the previous owner can no longer act on the job
+        await expectError(transferOwnership(job, ownerWallet.publicKey), "Unauthorized");
+    });
+
+    it("cannot transfer a job it does not own", async () => {
+        const job = await openJob();
+
+        await expectError(transferOwnership(job, providerWallet.publicKey, providerWallet), "Unauthorized");
+    });
+
+    it("cannot accept ownership without a proposal", async () => {
+        const job = await openJob();
+
+        await expectError(acceptOwnership(job, Keypair.generate()), "Unauthorized");
+    });
+
+    it("can cancel a pending transfer by proposing the default key", async () => {
+        const job = await openJob();
+        const newOwner = Keypair.generate();
+
+        await transferOwnership(job, newOwner.publicKey);
+        await transferOwnership(job, PublicKey.default);
+
+        const jobData = await program.account.job.fetch(job.job);
+        expect(jobData.pendingOwner.toBase58()).to.equal(PublicKey.default.toBase58());
+
+        await expectError(acceptOwnership(job, newOwner), "Unauthorized");
+    });
+});
+
+describe("market_v1 - tokenized jobs", () => {
+    let provider: anchor.AnchorProvider;
+    let program: Program<MarketV>;
+    let payer: Keypair;
+    let marketAccount: PublicKey;
+    let tokenMint: PublicKey;
+    let creditMint: PublicKey;
+    let providerWallet: Keypair;
+    let providerAccount: PublicKey;
+    let ownerWallet: Keypair;
+    let ownerTokenAccount: PublicKey;
+    let ownerCreditTokenAccount: PublicKey;
+
+    // Open a job minting its NFT to the owner
+    async function openJob(): Promise<any> {
+        const jobIndex = (await program.account.market.fetch(marketAccount)).jobIndex;
+        const [job,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job"), jobIndex.toArrayLike(Buffer, "le", 16)],
+            program.programId
+        );
+        const [jobNftMint,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("job_nft"), job.toBuffer()],
+            program.programId
+        );
+        const ownerNftAccount = getAssociatedTokenAddressSync(jobNftMint, ownerWallet.publicKey);
+        await program.methods.jobOpen(
+            "metadata example",
+            providerWallet.publicKey,
+            new anchor.BN(10),
+            new anchor.BN(100)
+        ).accountsStrict({
+            market: marketAccount,
+            job,
+            owner: ownerWallet.publicKey,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: ownerTokenAccount,
+            providerAccount,
+            rateCard: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: ownerCreditTokenAccount,
+            jobNftMint,
+            ownerNftAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        }).signers([ownerWallet]).rpc();
+        return { jobIndex, job, jobNftMint, ownerNftAccount };
+    }
+
+    async function expectError(call: Promise<any>, code: string) {
+        try {
+            await call;
+            assert.fail("the call should have failed");
+        } catch (error) {
+            expect((error as anchor.AnchorError).error.errorCode.code).to.equal(code);
+        }
+    }
+
+    // Move a job NFT to `holder`, returning the holder's NFT account
+    async function transferJobNft(job: any, holder: Keypair): Promise<PublicKey> {
+        const holderNftAccount = await createAssociatedTokenAccount(provider.connection, payer, job.jobNftMint, holder.publicKey);
+        await transfer(provider.connection, payer, job.ownerNftAccount, holderNftAccount, ownerWallet, 1);
+        return holderNftAccount;
+    }
+
+    function setRunwayThreshold(job: any, signer: Keypair, jobNftAccount: PublicKey | null): Promise<string> {
+        return program.methods.jobSetRunwayThreshold(job.jobIndex, new anchor.BN(3600)).accountsStrict({
+            market: marketAccount,
+            job: job.job,
+            owner: signer.publicKey,
+            jobNftAccount,
+        }).signers([signer]).rpc();
+    }
+
+    before(async () => {
+        provider = anchor.AnchorProvider.env();
+        anchor.setProvider(provider);
+        program = anchor.workspace.MarketV as Program<MarketV>;
+        payer = (provider.wallet as anchor.Wallet).payer;
+
+        [marketAccount,] = PublicKey.findProgramAddressSync([Buffer.from("market")], program.programId);
+        const marketData = await program.account.market.fetch(marketAccount);
+        tokenMint = marketData.tokenMint;
+        creditMint = marketData.creditMint;
+
+        ownerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(ownerWallet.publicKey, 10 ** 9)
+        );
+        ownerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, ownerWallet.publicKey);
+        await mintTo(provider.connection, payer, tokenMint, ownerTokenAccount, payer, 10 ** 6);
+        ownerCreditTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, creditMint, ownerWallet.publicKey);
+
+        providerWallet = Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(providerWallet.publicKey, 10 ** 9)
+        );
+        [providerAccount,] = PublicKey.findProgramAddressSync(
+            [Buffer.from("provider"), providerWallet.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods.providerAdd(
+            "https://example.com/",
+            new anchor.BN(0)
+        ).accountsStrict(
+            await providerAddAccounts(program, providerAccount, providerWallet.publicKey)
+        ).signers([providerWallet]).rpc();
+    });
+
+    it("can open a tokenized job", async () => {
+        const job = await openJob();
+
+        const jobData = await program.account.job.fetch(job.job);
+        expect(jobData.nftMint.toBase58()).to.equal(job.jobNftMint.toBase58());
+        expect((await getAccount(provider.connection, job.ownerNftAccount)).amount.toString()).to.equal("1");
+
+        // the supply is fixed at one
+        const mint = await getMint(provider.connection, job.jobNftMint);
+        expect(mint.supply.toString()).to.equal("1");
+        expect(mint.mintAuthority).to.be.null;
+    });
+
+    it("authorizes whoever holds the job NFT", async () => {
+        const job = await openJob();
+        const holder = Keypair.generate();
+        const holderNftAccount = await transferJobNft(job, holder);
+
+        await expectError(setRunwayThreshold(job, ownerWallet, job.ownerNftAccount), "Unauthorized");
+
+        await setRunwayThreshold(job, holder, holderNftAccount);
+        const jobData = await program.account.job.fetch(job.job);
+        expect(jobData.owner.toBase58()).to.equal(holder.publicKey.toBase58());
+        expect(jobData.runwayThreshold.toNumber()).to.equal(3600);
+    });
+
+    it("cannot act on a tokenized job without its NFT account", async () => {
+        const job = await openJob();
+
+        await expectError(setRunwayThreshold(job, ownerWallet, null), "JobNftRequired");
+    });
+
+    it("refunds the NFT holder when the provider rejects a tokenized job", async () => {
+        const job = await openJob();
+        const holder = Keypair.generate();
+        const holderNftAccount = await transferJobNft(job, holder);
+        const holderTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, tokenMint, holder.publicKey);
+        const holderCreditTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, creditMint, holder.publicKey);
+
+        const accounts = {
+            market: marketAccount,
+            job: job.job,
+            topupPolicy: PublicKey.findProgramAddressSync(
+                [Buffer.from("topup_policy"), job.job.toBuffer()],
+                program.programId
+            )[0],
+            owner: holder.publicKey,
+            jobNftMint: job.jobNftMint,
+            jobNftAccount: holderNftAccount,
+            tokenMint,
+            programTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("job_token"), tokenMint.toBuffer()],
+                program.programId
+            )[0],
+            userTokenAccount: holderTokenAccount,
+            providerAccount,
+            operator: null,
+            creditMint,
+            programCreditTokenAccount: PublicKey.findProgramAddressSync(
+                [Buffer.from("credit_token"), creditMint.toBuffer()],
+                program.programId
+            )[0],
+            userCreditTokenAccount: holderCreditTokenAccount,
+            issuerCreditTokenAccount: null,
+            authority: providerWallet.publicKey,
+            state: PublicKey.findProgramAddressSync(
+                [Buffer.from("state")],
+                anchor.workspace.OysterCredits.programId
+            )[0],
+            creditProgram: anchor.workspace.OysterCredits.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        };
+
+        // the previous owner no longer receives the refund
+        await expectError(
+            program.methods.jobReject(job.jobIndex).accountsStrict({
+                ...accounts,
+                owner: ownerWallet.publicKey,
+                userTokenAccount: ownerTokenAccount,
+                userCreditTokenAccount: ownerCreditTokenAccount,
+            }).signers([providerWallet]).rpc(),
+            "Unauthorized"
+        );
+
+        await program.methods.jobReject(job.jobIndex).accountsStrict(accounts)
+            .signers([providerWallet]).rpc();
+
+        expect((await getAccount(provider.connection, holderTokenAccount)).amount.toString()).to.equal("100");
+    });
+});